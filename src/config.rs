@@ -0,0 +1,71 @@
+//! Configuration shared by the transformations run by the processor
+use crate::models::TariffSchedule;
+
+/// Tunable parameters of the analyzers
+#[derive(Debug, Clone)]
+pub struct AnalysisConfig {
+    /// Number of characters of the geohash cells used by the grid analysis
+    pub grid_precision: usize,
+
+    /// Whether peak zones are also exported as a GeoJSON FeatureCollection
+    pub peak_zones_geojson: bool,
+
+    /// Whether peak zone features use the zone rectangle instead of its center point
+    pub zone_polygons: bool,
+
+    /// Trips whose meter distance is below this ratio of the straight-line distance
+    /// are flagged by the detour analysis
+    pub min_detour_ratio: f64,
+
+    /// Trips whose meter distance is above this ratio of the straight-line distance
+    /// are flagged by the detour analysis
+    pub max_detour_ratio: f64,
+
+    /// Maximum distance in meters between a pickup or dropoff and the point of
+    /// interest it gets tagged with
+    pub poi_radius_m: f64,
+
+    /// Whether swapped or sign-flipped coordinates are fixed before the analysis
+    pub repair_coordinates: bool,
+
+    /// Trips faster than this many miles per hour are ignored by the speed analysis
+    pub max_speed_mph: f64,
+
+    /// Number of most frequent pickup to dropoff cell pairs reported by the top
+    /// routes analysis
+    pub top_routes: usize,
+
+    /// Whether the time series analysis also builds a series of 15-minute intervals
+    pub quarter_hour_series: bool,
+
+    /// Whether a first pass over the input computes fare fences per zone and hour,
+    /// so the anomaly analysis can flag statistical outliers (enabled by default)
+    pub fare_outliers: bool,
+
+    /// Maximum number of flagged trips listed by the detour and anomaly analyses; the
+    /// earliest ones are kept, while their counters include every flagged trip
+    pub max_flagged_trips: usize,
+
+    /// Tariffs used to compute the expected metered fare of each trip
+    pub tariffs: TariffSchedule,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        Self {
+            grid_precision: 6,
+            peak_zones_geojson: false,
+            zone_polygons: false,
+            min_detour_ratio: 0.5,
+            max_detour_ratio: 5.0,
+            poi_radius_m: 100.0,
+            repair_coordinates: false,
+            max_speed_mph: 80.0,
+            top_routes: 100,
+            quarter_hour_series: false,
+            fare_outliers: true,
+            max_flagged_trips: 10_000,
+            tariffs: TariffSchedule::default(),
+        }
+    }
+}
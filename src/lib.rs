@@ -1,13 +1,31 @@
 //! # NYC Taxi Data Processor
+pub mod config;
 pub mod error;
 pub mod models;
+pub mod poi_index;
 pub mod processors;
+pub mod sketches;
 pub mod transformations;
 pub mod utils;
 
-pub use models::TaxiTrip;
+pub use config::AnalysisConfig;
+pub use models::{Tariff, TariffSchedule, TaxiTrip};
+pub use poi_index::PoiIndex;
 pub use processors::TaxiProcessor;
+pub use sketches::{
+    Counter, HyperLogLog, Moments, Percentiles, QuantileSketch, SpaceSaving, Spread,
+};
 pub use transformations::{
-    BatchAggregator, HourlyPattern, HourlyPatternAnalyzer, PaymentAnalyzer, PaymentStats, PeakZone,
-    PeakZoneAnalyzer,
+    ActiveTrips, AirportAnalysis, AirportAnalyzer, AirportTraffic, AnomalyAnalysis,
+    AnomalyAnalyzer, AnomalyFlag, AnomalySummary, BatchAggregator, CashTrips, ConcurrencyAnalysis,
+    ConcurrencyAnalyzer, DailyConcurrency, DailyPoint, DetourAnalysis, DetourAnalyzer, DetourFlag,
+    DetourStats, FareComponentAnalyzer, FareComponents, FareFenceAnalyzer, FareFences,
+    FlatFareViolation, GridAnalyzer, GridCell, HourlyNetFlow, HourlyPattern, HourlyPatternAnalyzer,
+    IntervalPoint, NegotiatedFareUsage, NetFlowAnalyzer, OccupancyAnalysis, OccupancyAnalyzer,
+    OccupancyStats, OdCell, OdMatrixAnalyzer, PaymentAnalyzer, PaymentStats, PeakZone,
+    PeakZoneAnalyzer, PoiActivity, PoiAnalyzer, RateCodeAnalysis, RateCodeAnalyzer, RateCodeStats,
+    RouteAnalyzer, RouteCount, SpeedAnalyzer, SpeedStats, StoreForwardAnalyzer, StoreForwardStats,
+    TariffAnalyzer, TariffDiscrepancy, TimeSeries, TimeSeriesAnalyzer, TipAnalysis, TipAnalyzer,
+    TipGroup, TipStats, TopRoutes, VendorAnalysis, VendorAnalyzer, VendorDayStats, VendorHourStats,
+    VendorMetrics, WeekdayHourAnalyzer, WeekdayHourStats, ZoneHourOccupancy, ZoneNetFlow,
 };
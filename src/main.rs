@@ -15,6 +15,58 @@ struct Cli {
     #[arg(short, long, default_value_t = 10000)]
     batch_size: usize,
 
+    /// Geohash precision (1-12) of the cells used by the grid analysis
+    #[arg(long, default_value_t = 6, value_parser = clap::value_parser!(u8).range(1..=12))]
+    grid_precision: u8,
+
+    /// Also export peak zones as a GeoJSON FeatureCollection
+    #[arg(long)]
+    geojson: bool,
+
+    /// Use zone polygons instead of center points in the peak zones GeoJSON (implies --geojson)
+    #[arg(long)]
+    zone_polygons: bool,
+
+    /// CSV file of points of interest (name, latitude, longitude[, category]) used to
+    /// tag pickups and dropoffs with their nearest POI
+    #[arg(long)]
+    poi_file: Option<String>,
+
+    /// Maximum distance in meters to the nearest point of interest
+    #[arg(long, default_value_t = 100.0)]
+    poi_radius: f64,
+
+    /// Fix swapped or sign-flipped coordinates that fall inside NYC once corrected
+    #[arg(long)]
+    repair_coordinates: bool,
+
+    /// Maximum plausible speed in miles per hour; faster trips are left out of the
+    /// speed analysis
+    #[arg(long, default_value_t = 80.0)]
+    max_speed: f64,
+
+    /// Number of most frequent routes between grid cells to report
+    #[arg(long, default_value_t = 100)]
+    top_routes: usize,
+
+    /// Also export a time series of 15-minute intervals
+    #[arg(long)]
+    quarter_hour_series: bool,
+
+    /// Skip the first pass that computes the fare fences, so fares that are
+    /// statistical outliers for their zone and hour are not flagged
+    #[arg(long)]
+    no_fare_outliers: bool,
+
+    /// Maximum number of flagged trips listed by the detour and anomaly analyses
+    #[arg(long, default_value_t = 10_000)]
+    max_flagged_trips: usize,
+
+    /// JSON file with the tariffs used to compute expected fares, each with its
+    /// effective date (defaults to the tariff in effect since 2012-09-04)
+    #[arg(long)]
+    tariff_file: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -66,7 +118,36 @@ fn main() -> Result<(), ProcessingError> {
         );
     }
 
-    let processor = TaxiProcessor::with_chunk_size(cli.batch_size);
+    let tariffs = match &cli.tariff_file {
+        Some(tariff_file) => TariffSchedule::from_json(tariff_file)?,
+        None => TariffSchedule::default(),
+    };
+
+    let config = AnalysisConfig {
+        grid_precision: cli.grid_precision as usize,
+        peak_zones_geojson: cli.geojson || cli.zone_polygons,
+        zone_polygons: cli.zone_polygons,
+        poi_radius_m: cli.poi_radius,
+        repair_coordinates: cli.repair_coordinates,
+        max_speed_mph: cli.max_speed,
+        top_routes: cli.top_routes,
+        quarter_hour_series: cli.quarter_hour_series,
+        fare_outliers: !cli.no_fare_outliers,
+        max_flagged_trips: cli.max_flagged_trips,
+        tariffs,
+        ..AnalysisConfig::default()
+    };
+    let mut processor = TaxiProcessor::with_config(cli.batch_size, config);
+
+    if let Some(poi_file) = &cli.poi_file {
+        let poi_index = PoiIndex::from_csv(poi_file)?;
+        println!(
+            "Tagging trips with the nearest of {} points of interest within {} m",
+            poi_index.len(),
+            cli.poi_radius
+        );
+        processor = processor.with_poi_index(poi_index);
+    }
 
     match cli.command {
         Commands::Process { input, output_dir } => {
@@ -74,7 +155,10 @@ fn main() -> Result<(), ProcessingError> {
                 "Processing {} with batch size of {} records",
                 input, cli.batch_size
             );
-            println!("Running all transformations: peak_zones, payment_analysis, hourly_patterns");
+            println!(
+                "Running all transformations: {}",
+                processors::TRANSFORMATION_NAMES.join(", ")
+            );
             processor.run_all_transformations(&input, output_dir.as_deref())?;
         }
         Commands::BatchProcess {
@@ -85,7 +169,10 @@ fn main() -> Result<(), ProcessingError> {
                 "Processing all CSV files in {} with batch size of {} records",
                 directory, cli.batch_size
             );
-            println!("Running all transformations: peak_zones, payment_analysis, hourly_patterns");
+            println!(
+                "Running all transformations: {}",
+                processors::TRANSFORMATION_NAMES.join(", ")
+            );
             processor.run_directory_all_transformations(&directory, &output_dir)?;
         }
     }
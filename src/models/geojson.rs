@@ -0,0 +1,84 @@
+//! Minimal GeoJSON data model used to export spatial results
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::utils::BoundingBox;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FeatureCollection {
+    /// Always "FeatureCollection"
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    pub features: Vec<Feature>,
+}
+
+impl FeatureCollection {
+    pub fn new(features: Vec<Feature>) -> Self {
+        Self {
+            kind: "FeatureCollection".to_string(),
+            features,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Feature {
+    /// Always "Feature"
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    pub geometry: Geometry,
+
+    /// Attributes shown by map tools for this feature
+    pub properties: Map<String, Value>,
+}
+
+impl Feature {
+    /// Creates a feature using the serialized fields of `properties` as its attributes
+    pub fn new<P: Serialize>(
+        geometry: Geometry,
+        properties: &P,
+    ) -> Result<Self, serde_json::Error> {
+        let properties = match serde_json::to_value(properties)? {
+            Value::Object(map) => map,
+            other => Map::from_iter([("value".to_string(), other)]),
+        };
+
+        Ok(Self {
+            kind: "Feature".to_string(),
+            geometry,
+            properties,
+        })
+    }
+}
+
+/// GeoJSON geometries, with positions in [longitude, latitude] order
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Geometry {
+    Point { coordinates: [f64; 2] },
+    Polygon { coordinates: Vec<Vec<[f64; 2]>> },
+}
+
+impl Geometry {
+    pub fn point(lat: f64, lng: f64) -> Self {
+        Geometry::Point {
+            coordinates: [lng, lat],
+        }
+    }
+
+    /// Closed rectangle covering the given bounding box
+    pub fn rectangle(bounds: &BoundingBox) -> Self {
+        Geometry::Polygon {
+            coordinates: vec![vec![
+                [bounds.min_lng, bounds.min_lat],
+                [bounds.max_lng, bounds.min_lat],
+                [bounds.max_lng, bounds.max_lat],
+                [bounds.min_lng, bounds.max_lat],
+                [bounds.min_lng, bounds.min_lat],
+            ]],
+        }
+    }
+}
@@ -1,4 +1,12 @@
 pub mod datetime_format;
+pub mod geojson;
+pub mod poi;
+pub mod run_report;
+pub mod tariff;
 pub mod taxi_trip;
 
+pub use geojson::{Feature, FeatureCollection, Geometry};
+pub use poi::Poi;
+pub use run_report::RunReport;
+pub use tariff::{Tariff, TariffSchedule};
 pub use taxi_trip::TaxiTrip;
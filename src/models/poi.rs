@@ -0,0 +1,19 @@
+//! Data model for a point of interest loaded from a local CSV file
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Poi {
+    /// Name used to tag the trips near this point (e.g. "Penn Station")
+    pub name: String,
+
+    /// Latitude of the point
+    pub latitude: f64,
+
+    /// Longitude of the point
+    pub longitude: f64,
+
+    /// Optional kind of point (subway station, hotel, venue...)
+    #[serde(default)]
+    pub category: Option<String>,
+}
@@ -0,0 +1,73 @@
+//! Data model for the counters collected while reading the input files
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::TaxiTrip;
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct RunReport {
+    /// Number of records read from the input files
+    pub records_read: usize,
+
+    /// Number of records discarded by the validation
+    pub records_rejected: usize,
+
+    /// Number of pickup or dropoff points fixed by the coordinate repair
+    pub coordinates_repaired: usize,
+
+    /// Number of pickup or dropoff points of valid records left outside the NYC area
+    pub invalid_coordinates: usize,
+
+    /// Records of vendors 1 and 2 discarded by the validation, per vendor and hour of
+    /// the day of the pickup
+    pub rejected_by_vendor_hour: BTreeMap<i32, BTreeMap<u32, usize>>,
+
+    /// Records of vendors 1 and 2 discarded by the validation, per vendor and pickup date
+    pub rejected_by_vendor_day: BTreeMap<i32, BTreeMap<NaiveDate, usize>>,
+}
+
+impl RunReport {
+    /// Counts a record discarded by the validation, attributing it to its vendor when
+    /// the vendor is known
+    pub fn add_rejected(&mut self, trip: &TaxiTrip) {
+        use chrono::Timelike;
+
+        self.records_rejected += 1;
+        if matches!(trip.vendor_id, 1 | 2) {
+            *self
+                .rejected_by_vendor_hour
+                .entry(trip.vendor_id)
+                .or_default()
+                .entry(trip.pickup_datetime.hour())
+                .or_insert(0) += 1;
+            *self
+                .rejected_by_vendor_day
+                .entry(trip.vendor_id)
+                .or_default()
+                .entry(trip.pickup_datetime.date_naive())
+                .or_insert(0) += 1;
+        }
+    }
+
+    /// Adds the counters of `other` into this report
+    pub fn merge(&mut self, other: &RunReport) {
+        self.records_read += other.records_read;
+        self.records_rejected += other.records_rejected;
+        self.coordinates_repaired += other.coordinates_repaired;
+        self.invalid_coordinates += other.invalid_coordinates;
+        for (vendor_id, hours) in &other.rejected_by_vendor_hour {
+            let target = self.rejected_by_vendor_hour.entry(*vendor_id).or_default();
+            for (hour, count) in hours {
+                *target.entry(*hour).or_insert(0) += count;
+            }
+        }
+        for (vendor_id, days) in &other.rejected_by_vendor_day {
+            let target = self.rejected_by_vendor_day.entry(*vendor_id).or_default();
+            for (date, count) in days {
+                *target.entry(*date).or_insert(0) += count;
+            }
+        }
+    }
+}
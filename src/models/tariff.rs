@@ -0,0 +1,111 @@
+//! Data model of the metered-fare tariffs of NYC yellow taxis
+//!
+//! A schedule holds the tariffs in effect over time, so trips are priced with the
+//! rates of their pickup date. The default schedule contains the rates set by the TLC
+//! on September 4, 2012; other schedules can be loaded from a JSON file.
+
+use crate::error::ProcessingError;
+use chrono::{Datelike, NaiveDate, Timelike};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+
+use super::TaxiTrip;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Tariff {
+    /// First day the tariff applies to
+    pub effective_date: NaiveDate,
+
+    /// Initial charge when the meter is engaged
+    pub flag_drop: f64,
+
+    /// Charge per 1/5 mile traveled at or above `slow_speed_mph`
+    pub per_fifth_mile: f64,
+
+    /// Charge per minute traveled below `slow_speed_mph` or stopped
+    pub per_minute_slow: f64,
+
+    /// Speed below which time is charged instead of distance
+    pub slow_speed_mph: f64,
+
+    /// Surcharge for trips starting between 8 PM and 6 AM
+    pub overnight_surcharge: f64,
+
+    /// Surcharge for trips starting between 4 PM and 8 PM on weekdays
+    pub rush_surcharge: f64,
+}
+
+impl Tariff {
+    /// Estimates the metered fare of the trip out of its distance and duration.
+    ///
+    /// Records only have the total distance and duration, so the trip is assumed to
+    /// cover its distance at the slow speed threshold, and the rest of the time is
+    /// charged as slow traffic.
+    pub fn expected_fare(&self, trip: &TaxiTrip) -> f64 {
+        let minutes = (trip.dropoff_datetime - trip.pickup_datetime).num_seconds() as f64 / 60.0;
+        let moving_minutes = trip.trip_distance / self.slow_speed_mph * 60.0;
+        let slow_minutes = (minutes - moving_minutes).max(0.0);
+
+        self.flag_drop
+            + (trip.trip_distance * 5.0).floor() * self.per_fifth_mile
+            + slow_minutes.floor() * self.per_minute_slow
+    }
+
+    /// Returns the overnight and rush hour surcharges that apply to the trip
+    pub fn expected_surcharge(&self, trip: &TaxiTrip) -> f64 {
+        let hour = trip.pickup_datetime.hour();
+        let weekday = trip.pickup_datetime.weekday().num_days_from_monday() < 5;
+
+        if !(6..20).contains(&hour) {
+            self.overnight_surcharge
+        } else if weekday && (16..20).contains(&hour) {
+            self.rush_surcharge
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Tariffs sorted by effective date
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TariffSchedule {
+    pub tariffs: Vec<Tariff>,
+}
+
+impl Default for TariffSchedule {
+    fn default() -> Self {
+        Self::new(vec![Tariff {
+            effective_date: NaiveDate::from_ymd_opt(2012, 9, 4).expect("valid date"),
+            flag_drop: 2.5,
+            per_fifth_mile: 0.5,
+            per_minute_slow: 0.5,
+            slow_speed_mph: 12.0,
+            overnight_surcharge: 0.5,
+            rush_surcharge: 1.0,
+        }])
+    }
+}
+
+impl TariffSchedule {
+    /// Creates a schedule out of tariffs in any order
+    pub fn new(mut tariffs: Vec<Tariff>) -> Self {
+        tariffs.sort_by_key(|tariff| tariff.effective_date);
+        Self { tariffs }
+    }
+
+    /// Loads a schedule from a JSON file with a `tariffs` array
+    pub fn from_json(path: &str) -> Result<Self, ProcessingError> {
+        let reader = BufReader::new(File::open(path)?);
+        let schedule: TariffSchedule = serde_json::from_reader(reader)?;
+        Ok(Self::new(schedule.tariffs))
+    }
+
+    /// Returns the tariff in effect on the given date, if any
+    pub fn tariff_for(&self, date: NaiveDate) -> Option<&Tariff> {
+        self.tariffs
+            .iter()
+            .rev()
+            .find(|tariff| tariff.effective_date <= date)
+    }
+}
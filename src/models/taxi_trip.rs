@@ -90,4 +90,12 @@ pub struct TaxiTrip {
     /// The total amount charged to passengers. Does not include cash tips.
     #[serde(rename = "total_amount")]
     pub total_amount: f64,
+
+    /// Nearest point of interest to the pickup, filled in by the POI enrichment
+    #[serde(skip)]
+    pub pickup_poi: Option<String>,
+
+    /// Nearest point of interest to the dropoff, filled in by the POI enrichment
+    #[serde(skip)]
+    pub dropoff_poi: Option<String>,
 }
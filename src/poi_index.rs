@@ -0,0 +1,139 @@
+//! Spatial index of points of interest used to enrich trips with their nearest POI
+
+use crate::error::ProcessingError;
+use crate::models::poi::Poi;
+use crate::models::TaxiTrip;
+use crate::utils::haversine_miles;
+use csv::Reader;
+use std::cmp::Ordering;
+
+/// Meters in a mile, used to convert haversine distances
+const METERS_PER_MILE: f64 = 1609.344;
+
+/// Meters per degree of latitude
+const METERS_PER_LAT_DEGREE: f64 = 110_574.0;
+
+/// Meters per degree of longitude at NYC latitude (about 40.7 degrees)
+const METERS_PER_LNG_DEGREE: f64 = 84_400.0;
+
+/// Two dimensional k-d tree over points of interest.
+///
+/// Coordinates are projected onto a local plane in meters, which is accurate enough
+/// at city scale, and the tree is stored implicitly: the median of each slice is its
+/// root, splitting by longitude on even depths and by latitude on odd ones.
+#[derive(Debug, Default)]
+pub struct PoiIndex {
+    /// Projected position of each POI together with its index in `pois`
+    nodes: Vec<([f64; 2], usize)>,
+    pois: Vec<Poi>,
+}
+
+/// Projects the coordinates onto a local plane in meters
+fn project(lat: f64, lng: f64) -> [f64; 2] {
+    [lng * METERS_PER_LNG_DEGREE, lat * METERS_PER_LAT_DEGREE]
+}
+
+impl PoiIndex {
+    /// Builds the index from the given points
+    pub fn new(pois: Vec<Poi>) -> Self {
+        let mut nodes: Vec<([f64; 2], usize)> = pois
+            .iter()
+            .enumerate()
+            .map(|(i, poi)| (project(poi.latitude, poi.longitude), i))
+            .collect();
+        Self::build(&mut nodes, 0);
+        Self { nodes, pois }
+    }
+
+    /// Loads the points from a CSV file with `name`, `latitude`, `longitude` and an
+    /// optional `category` column
+    pub fn from_csv(path: &str) -> Result<Self, ProcessingError> {
+        let mut reader = Reader::from_path(path)?;
+        let pois = reader.deserialize::<Poi>().collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(pois))
+    }
+
+    /// Number of indexed points
+    pub fn len(&self) -> usize {
+        self.pois.len()
+    }
+
+    /// Whether the index has no points
+    pub fn is_empty(&self) -> bool {
+        self.pois.is_empty()
+    }
+
+    /// Sorts `nodes` so that each median splits its slice along the axis of its depth
+    fn build(nodes: &mut [([f64; 2], usize)], depth: usize) {
+        if nodes.len() <= 1 {
+            return;
+        }
+
+        let axis = depth % 2;
+        let mid = nodes.len() / 2;
+        nodes.select_nth_unstable_by(mid, |a, b| {
+            a.0[axis].partial_cmp(&b.0[axis]).unwrap_or(Ordering::Equal)
+        });
+        let (left, right) = nodes.split_at_mut(mid);
+        Self::build(left, depth + 1);
+        Self::build(&mut right[1..], depth + 1);
+    }
+
+    /// Returns the nearest point within `radius_m` meters of the coordinates
+    pub fn nearest_within(&self, lat: f64, lng: f64, radius_m: f64) -> Option<&Poi> {
+        let target = project(lat, lng);
+        let mut best: Option<(f64, usize)> = None;
+        Self::search(&self.nodes, 0, target, radius_m * radius_m, &mut best);
+
+        // Confirm the match with the exact great-circle distance
+        best.map(|(_, i)| &self.pois[i]).filter(|poi| {
+            haversine_miles(lat, lng, poi.latitude, poi.longitude) * METERS_PER_MILE <= radius_m
+        })
+    }
+
+    /// Recursively looks for the closest node, pruning branches farther than the best
+    /// squared distance found so far (initially the squared radius)
+    fn search(
+        nodes: &[([f64; 2], usize)],
+        depth: usize,
+        target: [f64; 2],
+        max_dist_sq: f64,
+        best: &mut Option<(f64, usize)>,
+    ) {
+        if nodes.is_empty() {
+            return;
+        }
+
+        let axis = depth % 2;
+        let mid = nodes.len() / 2;
+        let (point, poi_index) = nodes[mid];
+        let dist_sq = (point[0] - target[0]).powi(2) + (point[1] - target[1]).powi(2);
+        let best_dist_sq = best.map_or(max_dist_sq, |(d, _)| d);
+        if dist_sq <= best_dist_sq {
+            *best = Some((dist_sq, poi_index));
+        }
+
+        let diff = target[axis] - point[axis];
+        let (near, far) = if diff < 0.0 {
+            (&nodes[..mid], &nodes[mid + 1..])
+        } else {
+            (&nodes[mid + 1..], &nodes[..mid])
+        };
+        Self::search(near, depth + 1, target, max_dist_sq, best);
+
+        let best_dist_sq = best.map_or(max_dist_sq, |(d, _)| d);
+        if diff * diff <= best_dist_sq {
+            Self::search(far, depth + 1, target, max_dist_sq, best);
+        }
+    }
+
+    /// Tags the pickup and dropoff of the trip with their nearest POI within `radius_m`
+    pub fn enrich_trip(&self, trip: &mut TaxiTrip, radius_m: f64) {
+        trip.pickup_poi = self
+            .nearest_within(trip.pickup_latitude, trip.pickup_longitude, radius_m)
+            .map(|poi| poi.name.clone());
+        trip.dropoff_poi = self
+            .nearest_within(trip.dropoff_latitude, trip.dropoff_longitude, radius_m)
+            .map(|poi| poi.name.clone());
+    }
+}
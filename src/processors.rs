@@ -1,46 +1,114 @@
 //! Module for processing NYC Taxi data with memory-efficient and parallel processing
+use crate::config::AnalysisConfig;
 use crate::error::ProcessingError;
-use crate::models::TaxiTrip;
-use crate::transformations::{BatchAggregator, MultiAnalyzer};
-use csv::Reader;
+use crate::models::{Feature, FeatureCollection, RunReport, TaxiTrip};
+use crate::poi_index::PoiIndex;
+use crate::transformations::{
+    BatchAggregator, FareFenceAnalyzer, GridCell, MultiAnalysisResults, MultiAnalyzer,
+};
+use csv::{Reader, Writer};
 use rayon::prelude::*;
+use serde::Serialize;
 use std::fs::{read_dir, File};
 use std::io::BufReader;
+use std::sync::Arc;
 use std::time::Instant;
 
+/// Names of the transformations run by the processor, in output order
+pub const TRANSFORMATION_NAMES: &[&str] = &[
+    "peak_zones",
+    "payment_analysis",
+    "hourly_patterns",
+    "od_matrix",
+    "grid_cells",
+    "detour",
+    "airports",
+    "poi_activity",
+    "weekday_hour",
+    "vendors",
+    "rate_codes",
+    "occupancy",
+    "speeds",
+    "top_routes",
+    "time_series",
+    "fare_components",
+    "anomalies",
+    "tariff_discrepancies",
+    "tips",
+    "store_forward",
+    "concurrency",
+    "net_flows",
+];
+
 pub struct TaxiProcessor {
     pub chunk_size: usize,
+    pub config: AnalysisConfig,
+    pub poi_index: Option<PoiIndex>,
 }
 
 impl TaxiProcessor {
     pub fn new() -> Self {
-        Self { chunk_size: 50_000 }
+        Self::with_chunk_size(50_000)
     }
 
     pub fn with_chunk_size(chunk_size: usize) -> Self {
-        Self { chunk_size }
+        Self::with_config(chunk_size, AnalysisConfig::default())
     }
 
+    pub fn with_config(chunk_size: usize, config: AnalysisConfig) -> Self {
+        Self {
+            chunk_size,
+            config,
+            poi_index: None,
+        }
+    }
+
+    /// Tags every trip with its nearest point of interest from `poi_index`
+    pub fn with_poi_index(mut self, poi_index: PoiIndex) -> Self {
+        self.poi_index = Some(poi_index);
+        self
+    }
+
+    /// Reads the file in batches of valid trips, repairing and enriching them if
+    /// configured, and returns the counters of the records read
     pub fn process_in_batches<F>(
         &self,
         file_path: &str,
         mut batch_processor: F,
-    ) -> Result<(), ProcessingError>
+    ) -> Result<RunReport, ProcessingError>
     where
         F: FnMut(&[TaxiTrip]) -> Result<(), ProcessingError>,
     {
+        use crate::utils::{is_valid_nyc_coordinate, repair_trip_coordinates, validate_trip};
+
         let file = File::open(file_path)?;
         let mut reader = Reader::from_reader(BufReader::new(file));
         let mut batch = Vec::with_capacity(self.chunk_size);
+        let mut report = RunReport::default();
 
         for result in reader.deserialize::<TaxiTrip>() {
-            let trip = result?;
-
-            if crate::utils::validate_trip(&trip).is_ok() {
+            let mut trip = result?;
+            report.records_read += 1;
+
+            if validate_trip(&trip).is_err() {
+                report.add_rejected(&trip);
+            } else {
+                if self.config.repair_coordinates {
+                    report.coordinates_repaired += repair_trip_coordinates(&mut trip);
+                }
+                for (lat, lng) in [
+                    (trip.pickup_latitude, trip.pickup_longitude),
+                    (trip.dropoff_latitude, trip.dropoff_longitude),
+                ] {
+                    if !is_valid_nyc_coordinate(lat, lng) {
+                        report.invalid_coordinates += 1;
+                    }
+                }
                 batch.push(trip);
             }
 
             if batch.len() >= self.chunk_size {
+                self.enrich_batch(&mut batch);
                 batch_processor(&batch)?;
                 batch.clear(); // Free memory immediately
             }
@@ -48,33 +116,51 @@ impl TaxiProcessor {
 
         // Process remaining batch
         if !batch.is_empty() {
+            self.enrich_batch(&mut batch);
             batch_processor(&batch)?;
         }
 
-        Ok(())
+        Ok(report)
+    }
+
+    /// Tags the trips of the batch with their nearest points of interest in parallel,
+    /// if a POI index is set
+    fn enrich_batch(&self, batch: &mut [TaxiTrip]) {
+        if let Some(poi_index) = &self.poi_index {
+            let radius_m = self.config.poi_radius_m;
+            batch
+                .par_iter_mut()
+                .for_each(|trip| poi_index.enrich_trip(trip, radius_m));
+        }
     }
 
     /// Generic streaming transformation runner using batch aggregators
-    fn run_streaming_transformation<A, T>(&self, input_path: &str) -> Result<T, ProcessingError>
+    /// created by `new_aggregator`
+    fn run_streaming_transformation<A, T, F>(
+        &self,
+        input_path: &str,
+        new_aggregator: F,
+    ) -> Result<(T, RunReport), ProcessingError>
     where
-        A: BatchAggregator<T> + Default + Send,
+        A: BatchAggregator<T> + Send,
         A::Accumulator: Send,
         T: Send,
+        F: Fn() -> A,
     {
         let mut batch_accumulators = Vec::new();
 
         // Process file in batches sequentially
-        self.process_in_batches(input_path, |batch| {
-            let mut local_aggregator = A::default();
+        let report = self.process_in_batches(input_path, |batch| {
+            let mut local_aggregator = new_aggregator();
             let accumulator = local_aggregator.process_batch(batch)?;
             batch_accumulators.push(accumulator);
             Ok(())
         })?;
 
         // Merge accumulators and finalize
-        let mut final_aggregator = A::default();
+        let mut final_aggregator = new_aggregator();
         final_aggregator.merge_accumulators(batch_accumulators)?;
-        final_aggregator.finalize()
+        Ok((final_aggregator.finalize()?, report))
     }
 
     /// Process a single file and run all transformations simultaneously using streaming batch processing
@@ -90,10 +176,26 @@ impl TaxiProcessor {
             "Processing {} using streaming batches of {} records",
             input_path, self.chunk_size
         );
-        println!("Running all transformations: peak_zones, payment_analysis, hourly_patterns");
+        println!(
+            "Running all transformations: {}",
+            TRANSFORMATION_NAMES.join(", ")
+        );
+
+        // Compute the fare fences first, unless fare outliers are skipped
+        let fare_fences = if self.config.fare_outliers {
+            let (fences, _) =
+                self.run_streaming_transformation(input_path, FareFenceAnalyzer::default)?;
+            println!("Computed fare fences for {} zone-hours", fences.len());
+            Some(Arc::new(fences))
+        } else {
+            None
+        };
 
         // Run single-pass transformation using MultiAnalyzer
-        let results = self.run_streaming_transformation::<MultiAnalyzer, _>(input_path)?;
+        let (mut results, report) = self.run_streaming_transformation(input_path, || {
+            MultiAnalyzer::with_config(&self.config).with_fare_fences(fare_fences.clone())
+        })?;
+        results.vendors.include_validation_rejects(&report);
 
         let processing_time = start_time.elapsed();
         println!(
@@ -101,36 +203,15 @@ impl TaxiProcessor {
             processing_time.as_secs_f64()
         );
 
-        // Write all three output files with thread count in filename
+        // Write all output files with thread count in filename
         let thread_count = rayon::current_num_threads();
-        let peak_zones_json = serde_json::to_string_pretty(&results.peak_zones)?;
-        let hourly_patterns_json = serde_json::to_string_pretty(&results.hourly_patterns)?;
-        let payment_analysis_json = serde_json::to_string_pretty(&results.payment_analysis)?;
-
-        std::fs::write(
-            format!("{}/peak_zones_{}_cpus.json", output_dir, thread_count),
-            peak_zones_json,
-        )?;
-        std::fs::write(
-            format!("{}/hourly_patterns_{}_cpus.json", output_dir, thread_count),
-            hourly_patterns_json,
-        )?;
-        std::fs::write(
-            format!("{}/payment_analysis_{}_cpus.json", output_dir, thread_count),
-            payment_analysis_json,
+        self.write_results(
+            &results,
+            &report,
+            output_dir,
+            &format!("{}_cpus", thread_count),
         )?;
 
-        println!("Results saved to:");
-        println!("  - {}/peak_zones_{}_cpus.json", output_dir, thread_count);
-        println!(
-            "  - {}/hourly_patterns_{}_cpus.json",
-            output_dir, thread_count
-        );
-        println!(
-            "  - {}/payment_analysis_{}_cpus.json",
-            output_dir, thread_count
-        );
-
         Ok(())
     }
 
@@ -162,9 +243,22 @@ impl TaxiProcessor {
             self.chunk_size
         );
 
+        // Compute the fare fences first, unless fare outliers are skipped
+        let fare_fences = if self.config.fare_outliers {
+            let (fences, _) = self
+                .run_directory_streaming_transformation(&csv_files, FareFenceAnalyzer::default)?;
+            println!("Computed fare fences for {} zone-hours", fences.len());
+            Some(Arc::new(fences))
+        } else {
+            None
+        };
+
         // Run directory-wide streaming transformation using MultiAnalyzer
-        let results =
-            self.run_directory_streaming_transformation::<MultiAnalyzer, _>(&csv_files)?;
+        let (mut results, report) = self
+            .run_directory_streaming_transformation(&csv_files, || {
+                MultiAnalyzer::with_config(&self.config).with_fare_fences(fare_fences.clone())
+            })?;
+        results.vendors.include_validation_rejects(&report);
 
         let processing_time = start_time.elapsed();
         println!(
@@ -174,60 +268,32 @@ impl TaxiProcessor {
 
         std::fs::create_dir_all(output_dir)?;
 
-        // Write all three output files with thread count in filename
+        // Write all output files with thread count in filename
         let thread_count = rayon::current_num_threads();
-        let peak_zones_json = serde_json::to_string_pretty(&results.peak_zones)?;
-        let hourly_patterns_json = serde_json::to_string_pretty(&results.hourly_patterns)?;
-        let payment_analysis_json = serde_json::to_string_pretty(&results.payment_analysis)?;
-
-        std::fs::write(
-            format!("{}/peak_zones_all_{}_cpus.json", output_dir, thread_count),
-            peak_zones_json,
-        )?;
-        std::fs::write(
-            format!(
-                "{}/hourly_patterns_all_{}_cpus.json",
-                output_dir, thread_count
-            ),
-            hourly_patterns_json,
-        )?;
-        std::fs::write(
-            format!(
-                "{}/payment_analysis_all_{}_cpus.json",
-                output_dir, thread_count
-            ),
-            payment_analysis_json,
+        self.write_results(
+            &results,
+            &report,
+            output_dir,
+            &format!("all_{}_cpus", thread_count),
         )?;
 
-        println!("Results saved to:");
-        println!(
-            "  - {}/peak_zones_all_{}_cpus.json",
-            output_dir, thread_count
-        );
-        println!(
-            "  - {}/hourly_patterns_all_{}_cpus.json",
-            output_dir, thread_count
-        );
-        println!(
-            "  - {}/payment_analysis_all_{}_cpus.json",
-            output_dir, thread_count
-        );
-
         Ok(())
     }
 
     /// Generic streaming transformation runner for multiple files with parallel processing
-    fn run_directory_streaming_transformation<A, T>(
+    fn run_directory_streaming_transformation<A, T, F>(
         &self,
         csv_files: &[String],
-    ) -> Result<T, ProcessingError>
+        new_aggregator: F,
+    ) -> Result<(T, RunReport), ProcessingError>
     where
-        A: BatchAggregator<T> + Default + Send + Sync,
+        A: BatchAggregator<T> + Send + Sync,
         A::Accumulator: Send,
         T: Send,
+        F: Fn() -> A + Sync,
     {
-        // Process files in parallel and collect accumulators from each file
-        let file_accumulators: Result<Vec<Vec<A::Accumulator>>, ProcessingError> = csv_files
+        // Process files in parallel and collect accumulators and reports from each file
+        let file_results = csv_files
             .par_iter()
             .map(|file_path| {
                 println!("Processing file: {}", file_path);
@@ -235,25 +301,176 @@ impl TaxiProcessor {
                 let mut batch_accumulators = Vec::new();
 
                 // Process this file in batches using streaming approach
-                self.process_in_batches(file_path, |batch| {
-                    let mut local_aggregator = A::default();
+                let report = self.process_in_batches(file_path, |batch| {
+                    let mut local_aggregator = new_aggregator();
                     let accumulator = local_aggregator.process_batch(batch)?;
                     batch_accumulators.push(accumulator);
                     Ok(())
                 })?;
 
-                Ok(batch_accumulators)
+                Ok((batch_accumulators, report))
             })
-            .collect();
-
-        // Flatten all accumulators from all files and batches
-        let all_accumulators: Vec<A::Accumulator> =
-            file_accumulators?.into_iter().flatten().collect();
+            .collect::<Result<Vec<_>, ProcessingError>>()?;
+
+        // Flatten all accumulators from all files and batches, adding up their reports
+        let mut all_accumulators: Vec<A::Accumulator> = Vec::new();
+        let mut report = RunReport::default();
+        for (batch_accumulators, file_report) in file_results {
+            all_accumulators.extend(batch_accumulators);
+            report.merge(&file_report);
+        }
 
         // Merge all accumulators and finalize
-        let mut final_aggregator = A::default();
+        let mut final_aggregator = new_aggregator();
         final_aggregator.merge_accumulators(all_accumulators)?;
-        final_aggregator.finalize()
+        Ok((final_aggregator.finalize()?, report))
+    }
+
+    /// Writes the run report and the results of every transformation into `output_dir`,
+    /// appending `label` to each file name
+    fn write_results(
+        &self,
+        results: &MultiAnalysisResults,
+        report: &RunReport,
+        output_dir: &str,
+        label: &str,
+    ) -> Result<(), ProcessingError> {
+        println!(
+            "Read {} records ({} rejected, {} coordinates repaired, {} invalid coordinates left)",
+            report.records_read,
+            report.records_rejected,
+            report.coordinates_repaired,
+            report.invalid_coordinates
+        );
+
+        let mut written = vec![
+            write_json(output_dir, "run_report", label, report)?,
+            write_json(output_dir, "peak_zones", label, &results.peak_zones)?,
+            write_json(
+                output_dir,
+                "hourly_patterns",
+                label,
+                &results.hourly_patterns,
+            )?,
+            write_json(
+                output_dir,
+                "payment_analysis",
+                label,
+                &results.payment_analysis,
+            )?,
+            write_json(output_dir, "od_matrix", label, &results.od_matrix)?,
+            write_csv(output_dir, "od_matrix", label, &results.od_matrix)?,
+            write_geojson(
+                output_dir,
+                "grid_cells",
+                label,
+                results.grid_cells.iter().map(GridCell::to_feature),
+            )?,
+            write_json(output_dir, "detour_stats", label, &results.detour.stats)?,
+            write_csv(
+                output_dir,
+                "detour_flags",
+                label,
+                &results.detour.flagged_trips,
+            )?,
+            write_json(output_dir, "airports", label, &results.airports)?,
+            write_json(output_dir, "weekday_hour", label, &results.weekday_hour)?,
+            write_json(output_dir, "vendor_hourly", label, &results.vendors.hourly)?,
+            write_json(output_dir, "vendor_daily", label, &results.vendors.daily)?,
+            write_json(output_dir, "rate_codes", label, &results.rate_codes)?,
+            write_json(output_dir, "occupancy", label, &results.occupancy)?,
+            write_json(output_dir, "speeds", label, &results.speeds)?,
+            write_json(output_dir, "top_routes", label, &results.top_routes)?,
+            write_json(
+                output_dir,
+                "daily_series",
+                label,
+                &results.time_series.daily,
+            )?,
+            write_json(
+                output_dir,
+                "fare_components",
+                label,
+                &results.fare_components,
+            )?,
+            write_csv(
+                output_dir,
+                "fare_components",
+                label,
+                &results.fare_components,
+            )?,
+            write_json(
+                output_dir,
+                "anomaly_summary",
+                label,
+                &results.anomalies.summary,
+            )?,
+            write_csv(
+                output_dir,
+                "anomaly_flags",
+                label,
+                &results.anomalies.flagged_trips,
+            )?,
+            write_json(
+                output_dir,
+                "tariff_discrepancies",
+                label,
+                &results.tariff_discrepancies,
+            )?,
+            write_json(output_dir, "tips", label, &results.tips)?,
+            write_json(output_dir, "store_forward", label, &results.store_forward)?,
+            write_csv(output_dir, "store_forward", label, &results.store_forward)?,
+            write_json(
+                output_dir,
+                "concurrency_daily",
+                label,
+                &results.concurrency.daily,
+            )?,
+            write_csv(
+                output_dir,
+                "active_trips",
+                label,
+                &results.concurrency.minutes,
+            )?,
+            write_json(output_dir, "net_flows", label, &results.net_flows)?,
+        ];
+
+        if self.poi_index.is_some() {
+            written.push(write_json(
+                output_dir,
+                "poi_activity",
+                label,
+                &results.poi_activity,
+            )?);
+        }
+
+        if let Some(quarter_hours) = &results.time_series.quarter_hours {
+            written.push(write_json(
+                output_dir,
+                "quarter_hour_series",
+                label,
+                quarter_hours,
+            )?);
+        }
+
+        if self.config.peak_zones_geojson {
+            written.push(write_geojson(
+                output_dir,
+                "peak_zones",
+                label,
+                results
+                    .peak_zones
+                    .iter()
+                    .map(|zone| zone.to_feature(self.config.zone_polygons)),
+            )?);
+        }
+
+        println!("Results saved to:");
+        for path in written {
+            println!("  - {}", path);
+        }
+
+        Ok(())
     }
 
     /// Get all CSV files in a directory
@@ -282,6 +499,53 @@ impl TaxiProcessor {
     }
 }
 
+/// Serializes `value` as pretty JSON into `<output_dir>/<name>_<label>.json`
+/// and returns the path of the written file
+fn write_json<T: Serialize + ?Sized>(
+    output_dir: &str,
+    name: &str,
+    label: &str,
+    value: &T,
+) -> Result<String, ProcessingError> {
+    let path = format!("{}/{}_{}.json", output_dir, name, label);
+    std::fs::write(&path, serde_json::to_string_pretty(value)?)?;
+    Ok(path)
+}
+
+/// Collects `features` into a GeoJSON FeatureCollection written into
+/// `<output_dir>/<name>_<label>.geojson` and returns the path of the written file
+fn write_geojson<I>(
+    output_dir: &str,
+    name: &str,
+    label: &str,
+    features: I,
+) -> Result<String, ProcessingError>
+where
+    I: Iterator<Item = Result<Feature, ProcessingError>>,
+{
+    let collection = FeatureCollection::new(features.collect::<Result<_, _>>()?);
+    let path = format!("{}/{}_{}.geojson", output_dir, name, label);
+    std::fs::write(&path, serde_json::to_string_pretty(&collection)?)?;
+    Ok(path)
+}
+
+/// Serializes `rows` as CSV records into `<output_dir>/<name>_<label>.csv`
+/// and returns the path of the written file
+fn write_csv<T: Serialize>(
+    output_dir: &str,
+    name: &str,
+    label: &str,
+    rows: &[T],
+) -> Result<String, ProcessingError> {
+    let path = format!("{}/{}_{}.csv", output_dir, name, label);
+    let mut writer = Writer::from_path(&path)?;
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+    Ok(path)
+}
+
 impl Default for TaxiProcessor {
     fn default() -> Self {
         Self::new()
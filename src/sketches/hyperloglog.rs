@@ -0,0 +1,141 @@
+//! HyperLogLog distinct-count sketch
+//!
+//! Estimates the number of distinct values added with a fixed amount of memory
+//! (`2^PRECISION` one-byte registers, about 1.6% standard error). Sketches that have
+//! seen few values keep only their non-empty registers (sparse mode) and switch to the
+//! full register array once it becomes cheaper, so the many small groups of the
+//! analyzers stay small. Merging takes the maximum of each register, which gives the
+//! same result as adding all the values to a single sketch.
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+
+/// Number of bits of the hash used to select a register
+const PRECISION: u32 = 12;
+
+/// Number of registers of the sketch
+const REGISTERS: usize = 1 << PRECISION;
+
+/// Number of non-empty registers above which the sketch switches to dense mode
+const SPARSE_LIMIT: usize = REGISTERS / 8;
+
+#[derive(Debug, Clone)]
+enum Registers {
+    /// Non-empty registers by index
+    Sparse(BTreeMap<u16, u8>),
+    /// All the registers
+    Dense(Vec<u8>),
+}
+
+/// Mergeable sketch of the number of distinct values
+#[derive(Debug, Clone)]
+pub struct HyperLogLog {
+    registers: Registers,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self {
+            registers: Registers::Sparse(BTreeMap::new()),
+        }
+    }
+}
+
+impl HyperLogLog {
+    /// Creates an empty sketch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a value to the sketch
+    pub fn add<T: Hash + ?Sized>(&mut self, value: &T) {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let index = (hash >> (64 - PRECISION)) as u16;
+        // Position of the first set bit of the remaining bits, capped when all are zero
+        let rank = ((hash << PRECISION).leading_zeros() + 1).min(64 - PRECISION + 1) as u8;
+        self.update(index, rank);
+    }
+
+    /// Adds all the values of `other` into this sketch
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        match &other.registers {
+            Registers::Sparse(registers) => {
+                for (index, rank) in registers {
+                    self.update(*index, *rank);
+                }
+            }
+            Registers::Dense(registers) => {
+                for (index, rank) in registers.iter().enumerate() {
+                    if *rank > 0 {
+                        self.update(index as u16, *rank);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the estimated number of distinct values added
+    pub fn estimate(&self) -> u64 {
+        let (sum, zeros) = match &self.registers {
+            Registers::Sparse(registers) => {
+                let sum: f64 = registers
+                    .values()
+                    .map(|rank| 2f64.powi(-(*rank as i32)))
+                    .sum();
+                let zeros = REGISTERS - registers.len();
+                (sum + zeros as f64, zeros)
+            }
+            Registers::Dense(registers) => {
+                let sum: f64 = registers
+                    .iter()
+                    .map(|rank| 2f64.powi(-(*rank as i32)))
+                    .sum();
+                let zeros = registers.iter().filter(|rank| **rank == 0).count();
+                (sum, zeros)
+            }
+        };
+
+        let m = REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let raw = alpha * m * m / sum;
+
+        // Linear counting is more accurate while many registers are still empty
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+
+        estimate.round() as u64
+    }
+
+    /// Whether the sketch keeps all its registers
+    pub fn is_dense(&self) -> bool {
+        matches!(self.registers, Registers::Dense(_))
+    }
+
+    /// Keeps the largest rank seen by a register
+    fn update(&mut self, index: u16, rank: u8) {
+        match &mut self.registers {
+            Registers::Sparse(registers) => {
+                let entry = registers.entry(index).or_insert(0);
+                *entry = (*entry).max(rank);
+
+                if registers.len() > SPARSE_LIMIT {
+                    let mut dense = vec![0; REGISTERS];
+                    for (index, rank) in registers.iter() {
+                        dense[*index as usize] = *rank;
+                    }
+                    self.registers = Registers::Dense(dense);
+                }
+            }
+            Registers::Dense(registers) => {
+                let register = &mut registers[index as usize];
+                *register = (*register).max(rank);
+            }
+        }
+    }
+}
@@ -0,0 +1,14 @@
+//! Mergeable summaries of value distributions
+//!
+//! Sketches keep a bounded amount of state per group while still being able to merge
+//! the results of different batches and files, so they can live inside the
+//! accumulators of the `BatchAggregator` transformations.
+pub mod hyperloglog;
+pub mod moments;
+pub mod quantile;
+pub mod space_saving;
+
+pub use hyperloglog::HyperLogLog;
+pub use moments::{Moments, Spread};
+pub use quantile::{Percentiles, QuantileSketch};
+pub use space_saving::{Counter, SpaceSaving};
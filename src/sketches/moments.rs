@@ -0,0 +1,119 @@
+//! Mergeable moment accumulator
+//!
+//! Keeps the count, mean and sum of squared deviations of a set of values with Welford's
+//! online algorithm, and combines partial results with Chan's parallel formula. Unlike
+//! summing values and squares, this stays numerically stable for large datasets and
+//! gives the same result no matter how the values are split into batches or threads.
+use serde::{Deserialize, Serialize};
+
+/// Mergeable accumulator of the count, mean, variance, minimum and maximum of a set of values
+#[derive(Debug, Clone)]
+pub struct Moments {
+    count: u64,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for Moments {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl Moments {
+    /// Creates an empty accumulator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a value to the accumulator. NaN values are ignored
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Adds all the values of `other` into this accumulator
+    pub fn merge(&mut self, other: &Moments) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = other.clone();
+            return;
+        }
+
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * self.count as f64 * other.count as f64 / count as f64;
+        self.count = count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// Number of values added
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Mean of the values, if any
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.mean)
+    }
+
+    /// Sample variance of the values, if there are at least two
+    pub fn variance(&self) -> Option<f64> {
+        (self.count > 1).then(|| self.m2 / (self.count - 1) as f64)
+    }
+
+    /// Sample standard deviation of the values, if there are at least two
+    pub fn std_dev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
+    /// Smallest value, if any
+    pub fn min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    /// Largest value, if any
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    /// Returns the standard deviation, minimum and maximum, using zero for the
+    /// ones that are not defined
+    pub fn spread(&self) -> Spread {
+        use crate::utils::round_to_2_decimals;
+
+        Spread {
+            std_dev: round_to_2_decimals(self.std_dev().unwrap_or(0.0)),
+            min: round_to_2_decimals(self.min().unwrap_or(0.0)),
+            max: round_to_2_decimals(self.max().unwrap_or(0.0)),
+        }
+    }
+}
+
+/// Dispersion of a distribution reported by the transformations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Spread {
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+}
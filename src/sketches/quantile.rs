@@ -0,0 +1,136 @@
+//! Quantile sketch based on DDSketch
+//!
+//! Values are stored in logarithmically sized buckets, so every quantile is returned
+//! with a relative error of at most `RELATIVE_ACCURACY`, no matter how long the tail of
+//! the distribution is. Merging two sketches just adds up their bucket counts, which
+//! makes the result independent of how the trips were split into batches.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Maximum relative error of the values returned by `QuantileSketch::quantile`
+pub const RELATIVE_ACCURACY: f64 = 0.01;
+
+/// Values closer to zero than this are counted as zero
+const MIN_INDEXABLE_VALUE: f64 = 1e-9;
+
+/// Ratio between the bounds of consecutive buckets
+fn gamma() -> f64 {
+    (1.0 + RELATIVE_ACCURACY) / (1.0 - RELATIVE_ACCURACY)
+}
+
+/// Returns the bucket that holds the given positive magnitude
+fn bucket_index(magnitude: f64) -> i32 {
+    (magnitude.ln() / gamma().ln()).ceil() as i32
+}
+
+/// Returns the value that represents the given bucket
+fn bucket_value(index: i32) -> f64 {
+    let gamma = gamma();
+    2.0 * gamma.powi(index) / (gamma + 1.0)
+}
+
+/// Mergeable sketch of a distribution of values
+#[derive(Debug, Clone, Default)]
+pub struct QuantileSketch {
+    positive: BTreeMap<i32, u64>,
+    negative: BTreeMap<i32, u64>,
+    zero_count: u64,
+    count: u64,
+}
+
+impl QuantileSketch {
+    /// Creates an empty sketch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a value to the sketch. NaN values are ignored
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+
+        if value > MIN_INDEXABLE_VALUE {
+            *self.positive.entry(bucket_index(value)).or_insert(0) += 1;
+        } else if value < -MIN_INDEXABLE_VALUE {
+            *self.negative.entry(bucket_index(-value)).or_insert(0) += 1;
+        } else {
+            self.zero_count += 1;
+        }
+        self.count += 1;
+    }
+
+    /// Adds all the values of `other` into this sketch
+    pub fn merge(&mut self, other: &QuantileSketch) {
+        for (index, count) in &other.positive {
+            *self.positive.entry(*index).or_insert(0) += count;
+        }
+        for (index, count) in &other.negative {
+            *self.negative.entry(*index).or_insert(0) += count;
+        }
+        self.zero_count += other.zero_count;
+        self.count += other.count;
+    }
+
+    /// Number of values added to the sketch
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Whether no values were added to the sketch
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the approximate value at the given quantile (0.0-1.0)
+    pub fn quantile(&self, quantile: f64) -> Option<f64> {
+        if self.count == 0 || !(0.0..=1.0).contains(&quantile) {
+            return None;
+        }
+
+        let rank = (quantile * (self.count - 1) as f64).floor() as u64;
+        let mut seen = 0;
+
+        // Negative values, from the largest magnitude to the smallest one
+        for (index, count) in self.negative.iter().rev() {
+            seen += count;
+            if seen > rank {
+                return Some(-bucket_value(*index));
+            }
+        }
+
+        seen += self.zero_count;
+        if seen > rank {
+            return Some(0.0);
+        }
+
+        for (index, count) in &self.positive {
+            seen += count;
+            if seen > rank {
+                return Some(bucket_value(*index));
+            }
+        }
+
+        None
+    }
+
+    /// Returns the median, 90th and 99th percentiles, or zeros if the sketch is empty
+    pub fn percentiles(&self) -> Percentiles {
+        use crate::utils::round_to_2_decimals;
+
+        let at = |quantile| round_to_2_decimals(self.quantile(quantile).unwrap_or(0.0));
+        Percentiles {
+            p50: at(0.5),
+            p90: at(0.9),
+            p99: at(0.99),
+        }
+    }
+}
+
+/// Percentiles of a distribution reported by the transformations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Percentiles {
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+}
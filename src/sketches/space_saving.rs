@@ -0,0 +1,160 @@
+//! Space-Saving heavy-hitter summary
+//!
+//! Keeps at most `capacity` counters and finds the most frequent items of a stream.
+//! Every counter overestimates the true frequency of its item by at most its `error`,
+//! and any item without a counter appeared at most `error_bound` times. Summaries are
+//! merged with the rule of Cafaro et al.: an item missing from a full summary is
+//! assumed to have that summary's smallest count, which keeps both guarantees.
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Number of counters of a summary created with `Default`
+pub const DEFAULT_CAPACITY: usize = 1_000;
+
+/// Estimated frequency of an item of a `SpaceSaving` summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Counter {
+    /// Upper bound of the number of times the item appeared
+    pub count: u64,
+    /// Maximum amount by which `count` exceeds the true number
+    pub error: u64,
+}
+
+/// Mergeable summary of the most frequent items of a stream
+#[derive(Debug, Clone)]
+pub struct SpaceSaving<K> {
+    capacity: usize,
+    counters: HashMap<K, Counter>,
+}
+
+impl<K: Hash + Eq + Clone> Default for SpaceSaving<K> {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl<K: Hash + Eq + Clone> SpaceSaving<K> {
+    /// Creates an empty summary that keeps at most `capacity` counters
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            counters: HashMap::new(),
+        }
+    }
+
+    /// Builds a summary out of exact counts, keeping the `capacity` most frequent items
+    pub fn from_counts<I: IntoIterator<Item = (K, u64)>>(capacity: usize, counts: I) -> Self {
+        let mut summary = Self::new(capacity);
+        summary.counters = counts
+            .into_iter()
+            .map(|(item, count)| (item, Counter { count, error: 0 }))
+            .collect();
+        summary.truncate();
+        summary
+    }
+
+    /// Adds one occurrence of an item
+    pub fn add(&mut self, item: K) {
+        if let Some(counter) = self.counters.get_mut(&item) {
+            counter.count += 1;
+            return;
+        }
+
+        if self.counters.len() < self.capacity {
+            self.counters.insert(item, Counter { count: 1, error: 0 });
+            return;
+        }
+
+        // Replace the item with the smallest count, inheriting its count as error
+        let (min_item, min_counter) = self
+            .counters
+            .iter()
+            .min_by_key(|(_, counter)| counter.count)
+            .map(|(item, counter)| (item.clone(), *counter))
+            .expect("a full summary has counters");
+        self.counters.remove(&min_item);
+        self.counters.insert(
+            item,
+            Counter {
+                count: min_counter.count + 1,
+                error: min_counter.count,
+            },
+        );
+    }
+
+    /// Adds all the occurrences summarized by `other` into this summary
+    pub fn merge(&mut self, other: &SpaceSaving<K>) {
+        // An item missing from a summary appeared at most `error_bound` times in it
+        let missing = |bound: u64| Counter {
+            count: bound,
+            error: bound,
+        };
+        let own_missing = missing(self.error_bound());
+        let other_missing = missing(other.error_bound());
+
+        for (item, counter) in self.counters.iter_mut() {
+            let other_counter = other.counters.get(item).unwrap_or(&other_missing);
+            counter.count += other_counter.count;
+            counter.error += other_counter.error;
+        }
+        for (item, other_counter) in &other.counters {
+            if !self.counters.contains_key(item) {
+                self.counters.insert(
+                    item.clone(),
+                    Counter {
+                        count: own_missing.count + other_counter.count,
+                        error: own_missing.error + other_counter.error,
+                    },
+                );
+            }
+        }
+        self.truncate();
+    }
+
+    /// Maximum number of times an item without a counter may have appeared
+    pub fn error_bound(&self) -> u64 {
+        if self.counters.len() < self.capacity {
+            0
+        } else {
+            self.counters
+                .values()
+                .map(|counter| counter.count)
+                .min()
+                .unwrap_or(0)
+        }
+    }
+
+    /// Returns the `n` items with the highest counts, from the most to the least frequent
+    pub fn top(&self, n: usize) -> Vec<(K, Counter)> {
+        let mut items: Vec<(K, Counter)> = self
+            .counters
+            .iter()
+            .map(|(item, counter)| (item.clone(), *counter))
+            .collect();
+        items.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(a.1.error.cmp(&b.1.error)));
+        items.truncate(n);
+        items
+    }
+
+    /// Number of counters in use
+    pub fn len(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// Whether no items were added
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty()
+    }
+
+    /// Drops the counters with the lowest counts until the summary fits its capacity
+    fn truncate(&mut self) {
+        if self.counters.len() <= self.capacity {
+            return;
+        }
+
+        let mut counters: Vec<(K, Counter)> = self.counters.drain().collect();
+        counters.sort_by_key(|(_, counter)| std::cmp::Reverse(counter.count));
+        counters.truncate(self.capacity);
+        self.counters = counters.into_iter().collect();
+    }
+}
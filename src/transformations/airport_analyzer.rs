@@ -0,0 +1,251 @@
+//! Airport analysis module
+//!
+//! Analyzes trips to and from JFK, LaGuardia and Newark airports, calculating the
+//! amount of trips, total revenue, total tolls and average duration for each airport,
+//! direction and hour. Also checks trips between JFK and Manhattan against the flat-fare
+//! rule and lists the earliest non-compliant ones.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use crate::utils::BoundingBox;
+use chrono::Timelike;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use super::airport_stats::{AirportAnalysis, AirportTraffic, FlatFareViolation};
+use super::batch_aggregator::BatchAggregator;
+
+/// Flat fare between JFK and Manhattan in effect since September 2012
+pub const JFK_FLAT_FARE: f64 = 52.0;
+
+/// Rate code of the JFK flat fare
+const JFK_RATE_CODE: i32 = 2;
+
+/// Rate code of trips to Newark
+const NEWARK_RATE_CODE: i32 = 3;
+
+/// Newark airport lies outside the zones known by `get_zone_id`
+const NEWARK_BOUNDS: BoundingBox = BoundingBox::new(40.67, 40.71, -74.19, -74.15);
+
+type AirportKey = (&'static str, &'static str, u32);
+type AirportStatsData = (usize, f64, f64, f64);
+type AirportStatsMap = HashMap<AirportKey, AirportStatsData>;
+
+/// Batch aggregator for airport analysis
+#[derive(Debug)]
+pub struct AirportAnalyzer {
+    max_flagged: usize,
+    airport_stats: AirportStatsMap,
+    jfk_manhattan_trips: usize,
+    violation_count: usize,
+    violations: Vec<FlatFareViolation>,
+}
+
+impl AirportAnalyzer {
+    /// Lists at most `max_flagged` flat-fare violations, keeping the earliest ones
+    pub fn with_max_flagged(mut self, max_flagged: usize) -> Self {
+        self.max_flagged = max_flagged;
+        self
+    }
+}
+
+impl Default for AirportAnalyzer {
+    fn default() -> Self {
+        Self {
+            max_flagged: crate::config::AnalysisConfig::default().max_flagged_trips,
+            airport_stats: HashMap::new(),
+            jfk_manhattan_trips: 0,
+            violation_count: 0,
+            violations: Vec::new(),
+        }
+    }
+}
+
+/// Order in which flat-fare violations are listed
+fn violation_order(violation: &FlatFareViolation) -> (chrono::DateTime<chrono::Utc>, i32) {
+    (violation.pickup_datetime, violation.vendor_id)
+}
+
+/// Returns the airport where the given point is located, if any
+fn airport_at(lat: f64, lng: f64) -> Option<&'static str> {
+    use crate::utils::get_zone_id;
+
+    match get_zone_id(lat, lng).as_str() {
+        "JFK_Airport" => Some("JFK"),
+        "LaGuardia_Airport" => Some("LaGuardia"),
+        _ if NEWARK_BOUNDS.contains(lat, lng) => Some("Newark"),
+        _ => None,
+    }
+}
+
+/// Returns every (airport, direction) pair the trip belongs to
+fn airport_legs(trip: &TaxiTrip) -> Vec<(&'static str, &'static str)> {
+    let mut legs = Vec::new();
+
+    if let Some(airport) = airport_at(trip.pickup_latitude, trip.pickup_longitude) {
+        legs.push((airport, "from"));
+    }
+
+    // The Newark rate code identifies trips to Newark even without valid coordinates
+    match airport_at(trip.dropoff_latitude, trip.dropoff_longitude) {
+        Some(airport) => legs.push((airport, "to")),
+        None if trip.rate_code_id == NEWARK_RATE_CODE => legs.push(("Newark", "to")),
+        None => {}
+    }
+
+    legs
+}
+
+/// Returns the route name if the trip goes between JFK and Manhattan
+fn jfk_manhattan_route(trip: &TaxiTrip) -> Option<&'static str> {
+    use crate::utils::get_zone_id;
+
+    let pickup_zone = get_zone_id(trip.pickup_latitude, trip.pickup_longitude);
+    let dropoff_zone = get_zone_id(trip.dropoff_latitude, trip.dropoff_longitude);
+    match (pickup_zone.as_str(), dropoff_zone.as_str()) {
+        ("JFK_Airport", "Manhattan") => Some("JFK->Manhattan"),
+        ("Manhattan", "JFK_Airport") => Some("Manhattan->JFK"),
+        _ => None,
+    }
+}
+
+type AirportAccumulator = (AirportStatsMap, usize, usize, Vec<FlatFareViolation>);
+
+/// Adds the values of `other` into `acc`, keeping at most `max_flagged` violations
+fn merge_airport_acc(acc: &mut AirportAccumulator, other: AirportAccumulator, max_flagged: usize) {
+    let (stats, jfk_manhattan_trips, violation_count, violations) = other;
+    for (key, (count, revenue, tolls, duration)) in stats {
+        let entry = acc.0.entry(key).or_insert((0, 0.0, 0.0, 0.0));
+        entry.0 += count;
+        entry.1 += revenue;
+        entry.2 += tolls;
+        entry.3 += duration;
+    }
+    acc.1 += jfk_manhattan_trips;
+    acc.2 += violation_count;
+    acc.3.extend(violations);
+    crate::utils::keep_smallest(&mut acc.3, max_flagged, violation_order);
+}
+
+impl BatchAggregator<AirportAnalysis> for AirportAnalyzer {
+    type Accumulator = AirportAccumulator;
+
+    /// Process a single batch and accumulate intermediate results
+    /// Filters out trips with non-positive total amounts or durations
+    /// Accumulates trip count, total revenue, total tolls and total duration per
+    /// airport, direction and hour, and counts the JFK-Manhattan trips that do not use
+    /// the flat fare, keeping the earliest ones aside
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::keep_smallest;
+
+        let max_flagged = self.max_flagged;
+        let batch_acc = batch
+            .par_iter()
+            .filter(|trip| trip.total_amount > 0.0 && trip.dropoff_datetime > trip.pickup_datetime)
+            .fold(
+                || (HashMap::new(), 0, 0, Vec::new()),
+                |mut acc: AirportAccumulator, trip| {
+                    let hour = trip.pickup_datetime.hour();
+                    let duration =
+                        (trip.dropoff_datetime - trip.pickup_datetime).num_seconds() as f64 / 60.0;
+
+                    for (airport, direction) in airport_legs(trip) {
+                        let entry = acc
+                            .0
+                            .entry((airport, direction, hour))
+                            .or_insert((0, 0.0, 0.0, 0.0));
+                        entry.0 += 1; // trip count
+                        entry.1 += trip.total_amount; // total revenue
+                        entry.2 += trip.tolls_amount; // total tolls
+                        entry.3 += duration; // total duration
+                    }
+
+                    if let Some(route) = jfk_manhattan_route(trip) {
+                        acc.1 += 1;
+                        let compliant = trip.rate_code_id == JFK_RATE_CODE
+                            && (trip.fare_amount - JFK_FLAT_FARE).abs() < 0.01;
+                        if !compliant {
+                            acc.2 += 1;
+                            acc.3.push(FlatFareViolation {
+                                pickup_datetime: trip.pickup_datetime,
+                                vendor_id: trip.vendor_id,
+                                route: route.to_string(),
+                                rate_code_id: trip.rate_code_id,
+                                fare_amount: trip.fare_amount,
+                                expected_fare: JFK_FLAT_FARE,
+                            });
+                            keep_smallest(&mut acc.3, max_flagged, violation_order);
+                        }
+                    }
+                    acc
+                },
+            )
+            .reduce(
+                || (HashMap::new(), 0, 0, Vec::new()),
+                |mut acc1, acc2| {
+                    merge_airport_acc(&mut acc1, acc2, max_flagged);
+                    acc1
+                },
+            );
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for (stats, jfk_manhattan_trips, violation_count, violations) in accumulators {
+            for (key, (count, revenue, tolls, duration)) in stats {
+                let entry = self.airport_stats.entry(key).or_insert((0, 0.0, 0.0, 0.0));
+                entry.0 += count;
+                entry.1 += revenue;
+                entry.2 += tolls;
+                entry.3 += duration;
+            }
+            self.jfk_manhattan_trips += jfk_manhattan_trips;
+            self.violation_count += violation_count;
+            self.violations.extend(violations);
+            crate::utils::keep_smallest(&mut self.violations, self.max_flagged, violation_order);
+        }
+        Ok(())
+    }
+
+    /// Generates the airport traffic sorted by airport, direction and hour, and the
+    /// earliest flat-fare violations sorted by pickup time and vendor
+    fn finalize(self) -> Result<AirportAnalysis, ProcessingError> {
+        use crate::utils::round_to_2_decimals;
+
+        let mut traffic: Vec<AirportTraffic> = self
+            .airport_stats
+            .into_iter()
+            .map(
+                |((airport, direction, hour), (count, revenue, tolls, duration))| AirportTraffic {
+                    airport: airport.to_string(),
+                    direction: direction.to_string(),
+                    hour,
+                    trip_count: count,
+                    total_revenue: round_to_2_decimals(revenue),
+                    total_tolls: round_to_2_decimals(tolls),
+                    avg_duration: round_to_2_decimals(duration / count as f64),
+                },
+            )
+            .collect();
+
+        traffic.sort_by(|a, b| {
+            (&a.airport, &a.direction, a.hour).cmp(&(&b.airport, &b.direction, b.hour))
+        });
+
+        let mut flat_fare_violations = self.violations;
+        flat_fare_violations.sort_by_key(violation_order);
+        flat_fare_violations.truncate(self.max_flagged);
+
+        Ok(AirportAnalysis {
+            traffic,
+            jfk_manhattan_trips: self.jfk_manhattan_trips,
+            flat_fare_violation_count: self.violation_count,
+            flat_fare_violations_listed: flat_fare_violations.len(),
+            flat_fare_violations,
+        })
+    }
+}
@@ -0,0 +1,69 @@
+//! Defines the structs produced by the airport analysis.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::datetime_format;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AirportAnalysis {
+    /// Traffic to and from each airport per hour
+    pub traffic: Vec<AirportTraffic>,
+
+    /// Number of trips between JFK and Manhattan
+    pub jfk_manhattan_trips: usize,
+
+    /// Number of JFK-Manhattan trips that did not follow the flat-fare rule
+    pub flat_fare_violation_count: usize,
+
+    /// Number of violations listed, at most the configured maximum
+    pub flat_fare_violations_listed: usize,
+
+    /// Earliest JFK-Manhattan trips that did not follow the flat-fare rule
+    pub flat_fare_violations: Vec<FlatFareViolation>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AirportTraffic {
+    /// Airport name (JFK, LaGuardia or Newark)
+    pub airport: String,
+
+    /// Either "to" or "from" the airport
+    pub direction: String,
+
+    /// Hour of the day (0-23)
+    pub hour: u32,
+
+    /// Number of trips
+    pub trip_count: usize,
+
+    /// Total revenue generated
+    pub total_revenue: f64,
+
+    /// Total amount paid in tolls
+    pub total_tolls: f64,
+
+    /// Average trip duration in minutes
+    pub avg_duration: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlatFareViolation {
+    /// Date and time when the meter was engaged
+    #[serde(with = "datetime_format")]
+    pub pickup_datetime: DateTime<Utc>,
+
+    /// Vendor that provided the record
+    pub vendor_id: i32,
+
+    /// Either "JFK->Manhattan" or "Manhattan->JFK"
+    pub route: String,
+
+    /// Rate code in effect at the end of the trip
+    pub rate_code_id: i32,
+
+    /// Fare charged by the meter
+    pub fare_amount: f64,
+
+    /// Flat fare that should have been charged
+    pub expected_fare: f64,
+}
@@ -0,0 +1,255 @@
+//! Anomaly analysis module
+//!
+//! Flags trips matching any of these patterns, each one identified by a reason code:
+//! a total amount that differs from the sum of its components by more than one cent,
+//! zero distance with a high fare, durations over 24 hours, negative durations, speeds
+//! above 100 mph and, when the fences of a previous pass are available, fares that are
+//! statistical outliers for their pickup zone and hour.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use chrono::Timelike;
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use super::anomaly_stats::{AnomalyAnalysis, AnomalyFlag, AnomalySummary};
+use super::batch_aggregator::BatchAggregator;
+use super::fare_fences::FareFences;
+
+/// Reason code of totals that do not match the sum of their components
+pub const TOTAL_MISMATCH: &str = "TOTAL_MISMATCH";
+/// Reason code of trips without distance and a high fare
+pub const ZERO_DISTANCE_HIGH_FARE: &str = "ZERO_DISTANCE_HIGH_FARE";
+/// Reason code of trips longer than a day
+pub const DURATION_OVER_24H: &str = "DURATION_OVER_24H";
+/// Reason code of trips whose dropoff is before the pickup
+pub const NEGATIVE_DURATION: &str = "NEGATIVE_DURATION";
+/// Reason code of trips faster than `MAX_SPEED_MPH`
+pub const SPEED_OVER_100MPH: &str = "SPEED_OVER_100MPH";
+/// Reason code of fares outside the fences of their zone and hour
+pub const FARE_OUTLIER: &str = "FARE_OUTLIER";
+
+/// Largest accepted difference between the total and the sum of its components
+const MAX_TOTAL_DIFFERENCE: f64 = 0.01;
+
+/// Fares above this amount are considered high for a trip without distance
+const ZERO_DISTANCE_MAX_FARE: f64 = 50.0;
+
+/// Longest plausible trip in seconds
+const MAX_DURATION_SECONDS: i64 = 24 * 60 * 60;
+
+/// Fastest plausible speed in miles per hour
+const MAX_SPEED_MPH: f64 = 100.0;
+
+type AnomalyAccumulator = (
+    usize,
+    usize,
+    BTreeMap<&'static str, usize>,
+    Vec<AnomalyFlag>,
+);
+
+/// Batch aggregator for anomaly analysis
+#[derive(Debug)]
+pub struct AnomalyAnalyzer {
+    fences: Option<Arc<FareFences>>,
+    max_flagged: usize,
+    trips_checked: usize,
+    trips_flagged: usize,
+    reason_counts: BTreeMap<&'static str, usize>,
+    flagged_trips: Vec<AnomalyFlag>,
+}
+
+impl AnomalyAnalyzer {
+    /// Creates an analyzer that also flags fares outside the given fences
+    pub fn with_fences(fences: Option<Arc<FareFences>>) -> Self {
+        Self {
+            fences,
+            ..Self::default()
+        }
+    }
+
+    /// Lists at most `max_flagged` flagged trips, keeping the earliest ones
+    pub fn with_max_flagged(mut self, max_flagged: usize) -> Self {
+        self.max_flagged = max_flagged;
+        self
+    }
+
+    /// Maximum number of flagged trips listed
+    pub fn max_flagged(&self) -> usize {
+        self.max_flagged
+    }
+}
+
+impl Default for AnomalyAnalyzer {
+    fn default() -> Self {
+        Self {
+            fences: None,
+            max_flagged: crate::config::AnalysisConfig::default().max_flagged_trips,
+            trips_checked: 0,
+            trips_flagged: 0,
+            reason_counts: BTreeMap::new(),
+            flagged_trips: Vec::new(),
+        }
+    }
+}
+
+/// Order in which flagged trips are listed
+fn flag_order(flag: &AnomalyFlag) -> (chrono::DateTime<chrono::Utc>, i32) {
+    (flag.pickup_datetime, flag.vendor_id)
+}
+
+/// Sum of the components that make up the total amount
+fn component_sum(trip: &TaxiTrip) -> f64 {
+    trip.fare_amount
+        + trip.extra
+        + trip.mta_tax
+        + trip.improvement_surcharge.unwrap_or(0.0)
+        + trip.tip_amount
+        + trip.tolls_amount
+}
+
+/// Returns the reason codes of every anomaly pattern the trip matches
+fn anomaly_reasons(
+    trip: &TaxiTrip,
+    zone_id: &str,
+    fences: Option<&FareFences>,
+) -> Vec<&'static str> {
+    let mut reasons = Vec::new();
+    let seconds = (trip.dropoff_datetime - trip.pickup_datetime).num_seconds();
+
+    // Rounded to cents first, so float errors do not count as a difference
+    let difference = ((trip.total_amount - component_sum(trip)) * 100.0)
+        .round()
+        .abs()
+        / 100.0;
+    if difference > MAX_TOTAL_DIFFERENCE {
+        reasons.push(TOTAL_MISMATCH);
+    }
+    if trip.trip_distance == 0.0 && trip.fare_amount > ZERO_DISTANCE_MAX_FARE {
+        reasons.push(ZERO_DISTANCE_HIGH_FARE);
+    }
+    if seconds > MAX_DURATION_SECONDS {
+        reasons.push(DURATION_OVER_24H);
+    }
+    if seconds < 0 {
+        reasons.push(NEGATIVE_DURATION);
+    }
+    if seconds > 0 && trip.trip_distance / (seconds as f64 / 3600.0) > MAX_SPEED_MPH {
+        reasons.push(SPEED_OVER_100MPH);
+    }
+    if let Some((lower, upper)) =
+        fences.and_then(|fences| fences.get(zone_id, trip.pickup_datetime.hour()))
+    {
+        if trip.fare_amount < lower || trip.fare_amount > upper {
+            reasons.push(FARE_OUTLIER);
+        }
+    }
+
+    reasons
+}
+
+impl BatchAggregator<AnomalyAnalysis> for AnomalyAnalyzer {
+    type Accumulator = AnomalyAccumulator;
+
+    /// Process a single batch and accumulate intermediate results
+    /// Counts the trips checked, the trips flagged and the trips flagged for each
+    /// reason, and keeps the earliest flagged trips
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::{get_zone_id, keep_smallest};
+
+        let fences = self.fences.as_deref();
+        let max_flagged = self.max_flagged;
+        let batch_acc = batch
+            .par_iter()
+            .fold(
+                || (0, 0, BTreeMap::new(), Vec::new()),
+                |(mut checked, mut flagged_count, mut counts, mut flagged): AnomalyAccumulator,
+                 trip| {
+                    checked += 1;
+                    let zone_id = get_zone_id(trip.pickup_latitude, trip.pickup_longitude);
+                    let reasons = anomaly_reasons(trip, &zone_id, fences);
+                    if !reasons.is_empty() {
+                        flagged_count += 1;
+                        for reason in &reasons {
+                            *counts.entry(*reason).or_insert(0) += 1;
+                        }
+                        flagged.push(AnomalyFlag {
+                            pickup_datetime: trip.pickup_datetime,
+                            vendor_id: trip.vendor_id,
+                            pickup_zone: zone_id,
+                            trip_distance: trip.trip_distance,
+                            duration_minutes: (trip.dropoff_datetime - trip.pickup_datetime)
+                                .num_seconds() as f64
+                                / 60.0,
+                            fare_amount: trip.fare_amount,
+                            total_amount: trip.total_amount,
+                            component_sum: (component_sum(trip) * 100.0).round() / 100.0,
+                            reasons: reasons.join("|"),
+                        });
+                        keep_smallest(&mut flagged, max_flagged, flag_order);
+                    }
+                    (checked, flagged_count, counts, flagged)
+                },
+            )
+            .reduce(
+                || (0, 0, BTreeMap::new(), Vec::new()),
+                |(checked1, flagged_count1, mut counts1, mut flagged1),
+                 (checked2, flagged_count2, counts2, flagged2)| {
+                    for (reason, count) in counts2 {
+                        *counts1.entry(reason).or_insert(0) += count;
+                    }
+                    flagged1.extend(flagged2);
+                    keep_smallest(&mut flagged1, max_flagged, flag_order);
+                    (
+                        checked1 + checked2,
+                        flagged_count1 + flagged_count2,
+                        counts1,
+                        flagged1,
+                    )
+                },
+            );
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        use crate::utils::keep_smallest;
+
+        for (checked, flagged_count, counts, flagged) in accumulators {
+            self.trips_checked += checked;
+            self.trips_flagged += flagged_count;
+            for (reason, count) in counts {
+                *self.reason_counts.entry(reason).or_insert(0) += count;
+            }
+            self.flagged_trips.extend(flagged);
+            keep_smallest(&mut self.flagged_trips, self.max_flagged, flag_order);
+        }
+        Ok(())
+    }
+
+    /// Generates the summary and the earliest flagged trips sorted by pickup time and
+    /// vendor
+    fn finalize(mut self) -> Result<AnomalyAnalysis, ProcessingError> {
+        self.flagged_trips.sort_by_key(flag_order);
+        self.flagged_trips.truncate(self.max_flagged);
+
+        Ok(AnomalyAnalysis {
+            summary: AnomalySummary {
+                trips_checked: self.trips_checked,
+                trips_flagged: self.trips_flagged,
+                trips_listed: self.flagged_trips.len(),
+                fare_outliers_checked: self.fences.is_some(),
+                reason_counts: self
+                    .reason_counts
+                    .into_iter()
+                    .map(|(reason, count)| (reason.to_string(), count))
+                    .collect(),
+            },
+            flagged_trips: self.flagged_trips,
+        })
+    }
+}
@@ -0,0 +1,64 @@
+//! Defines the structs produced by the anomaly analysis.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::models::datetime_format;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnomalyAnalysis {
+    /// Number of trips flagged for each reason
+    pub summary: AnomalySummary,
+
+    /// Earliest trips matching at least one anomaly pattern
+    pub flagged_trips: Vec<AnomalyFlag>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnomalySummary {
+    /// Number of trips checked
+    pub trips_checked: usize,
+
+    /// Number of trips flagged for at least one reason
+    pub trips_flagged: usize,
+
+    /// Number of flagged trips listed, at most the configured maximum
+    pub trips_listed: usize,
+
+    /// Whether fares were compared with the fences of their zone and hour
+    pub fare_outliers_checked: bool,
+
+    /// Number of trips flagged for each reason code
+    pub reason_counts: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyFlag {
+    /// Date and time when the meter was engaged
+    #[serde(with = "datetime_format")]
+    pub pickup_datetime: DateTime<Utc>,
+
+    /// Vendor that provided the record
+    pub vendor_id: i32,
+
+    /// Zone where the meter was engaged
+    pub pickup_zone: String,
+
+    /// Distance in miles reported by the meter
+    pub trip_distance: f64,
+
+    /// Trip duration in minutes, negative if the dropoff is before the pickup
+    pub duration_minutes: f64,
+
+    /// Time-and-distance fare
+    pub fare_amount: f64,
+
+    /// Total amount charged
+    pub total_amount: f64,
+
+    /// Sum of fare, extra, MTA tax, improvement surcharge, tip and tolls
+    pub component_sum: f64,
+
+    /// Reason codes separated by `|`
+    pub reasons: String,
+}
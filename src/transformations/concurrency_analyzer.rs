@@ -0,0 +1,143 @@
+//! Concurrent trips analysis module
+//!
+//! Counts the trips in progress at each minute with a sweep line over the pickup and
+//! dropoff times. Each trip adds one to the minute of its pickup and subtracts one from
+//! the minute of its dropoff, so batches and files only produce arrays of per-minute
+//! deltas that are added together, and trips crossing their boundaries are counted as
+//! if all the records had been read at once. Also reports the peak and average
+//! concurrency of each day.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use chrono::{DateTime, NaiveDate, Utc};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+use super::batch_aggregator::BatchAggregator;
+use super::concurrency_stats::{ActiveTrips, ConcurrencyAnalysis, DailyConcurrency};
+
+/// Longest trip counted, so records with broken dropoff times do not stay active
+/// for days
+const MAX_DURATION_MINUTES: i64 = 24 * 60;
+
+/// Change in the number of active trips at the start of each minute since the epoch
+type DeltaMap = HashMap<i64, i64>;
+type DailyData = (i64, i64, i64, usize);
+
+/// Batch aggregator for concurrent trips analysis
+#[derive(Debug, Default)]
+pub struct ConcurrencyAnalyzer {
+    deltas: DeltaMap,
+}
+
+/// Returns the start of the given minute since the epoch
+fn minute_start(minute: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(minute * 60, 0).unwrap_or_default()
+}
+
+/// Adds the deltas of `source` into `target`
+fn merge_deltas(target: &mut DeltaMap, source: DeltaMap) {
+    for (minute, delta) in source {
+        *target.entry(minute).or_insert(0) += delta;
+    }
+}
+
+impl BatchAggregator<ConcurrencyAnalysis> for ConcurrencyAnalyzer {
+    type Accumulator = DeltaMap;
+
+    /// Process a single batch and accumulate intermediate results
+    /// Filters out trips with non-positive durations or lasting more than 24 hours
+    /// Adds one at the minute of the pickup and subtracts one at the minute of the
+    /// dropoff. Trips starting and ending within the same minute are active during it
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        let batch_acc = batch
+            .par_iter()
+            .filter(|trip| {
+                let minutes = (trip.dropoff_datetime - trip.pickup_datetime).num_minutes();
+                trip.dropoff_datetime > trip.pickup_datetime && minutes <= MAX_DURATION_MINUTES
+            })
+            .fold(HashMap::new, |mut acc: DeltaMap, trip| {
+                let start = trip.pickup_datetime.timestamp().div_euclid(60);
+                let end = trip
+                    .dropoff_datetime
+                    .timestamp()
+                    .div_euclid(60)
+                    .max(start + 1);
+                *acc.entry(start).or_insert(0) += 1;
+                *acc.entry(end).or_insert(0) -= 1;
+                acc
+            })
+            .reduce(HashMap::new, |mut acc1, acc2| {
+                merge_deltas(&mut acc1, acc2);
+                acc1
+            });
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for accumulator in accumulators {
+            merge_deltas(&mut self.deltas, accumulator);
+        }
+        Ok(())
+    }
+
+    /// Sweeps the deltas in time order to get the trips in progress at each minute,
+    /// skipping the stretches without active trips, and the concurrency of each day
+    fn finalize(self) -> Result<ConcurrencyAnalysis, ProcessingError> {
+        use crate::utils::round_to_2_decimals;
+
+        let deltas: Vec<(i64, i64)> = self
+            .deltas
+            .into_iter()
+            .filter(|(_, delta)| *delta != 0)
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .collect();
+
+        let mut minutes = Vec::new();
+        // Peak, minute of the peak, sum of active trips and active minutes per day
+        let mut daily: BTreeMap<NaiveDate, DailyData> = BTreeMap::new();
+        let mut active = 0;
+        for (index, (minute, delta)) in deltas.iter().enumerate() {
+            active += delta;
+            if active <= 0 {
+                continue;
+            }
+            let next = deltas.get(index + 1).map_or(minute + 1, |(next, _)| *next);
+            for current in *minute..next {
+                let start = minute_start(current);
+                let entry = daily.entry(start.date_naive()).or_insert((0, 0, 0, 0));
+                if active > entry.0 {
+                    entry.0 = active;
+                    entry.1 = current;
+                }
+                entry.2 += active;
+                entry.3 += 1;
+                minutes.push(ActiveTrips {
+                    minute: start,
+                    active_trips: active,
+                });
+            }
+        }
+
+        let daily = daily
+            .into_iter()
+            .map(
+                |(date, (peak, peak_minute, active_sum, active_minutes))| DailyConcurrency {
+                    date,
+                    active_minutes,
+                    peak_active_trips: peak,
+                    peak_minute: minute_start(peak_minute),
+                    // Minutes of the day without active trips count as zero
+                    avg_active_trips: round_to_2_decimals(active_sum as f64 / (24.0 * 60.0)),
+                },
+            )
+            .collect();
+
+        Ok(ConcurrencyAnalysis { daily, minutes })
+    }
+}
@@ -0,0 +1,42 @@
+//! Defines the structs produced by the concurrent trips analysis.
+use crate::models::datetime_format;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConcurrencyAnalysis {
+    /// Peak and average concurrency of each day, sorted by date
+    pub daily: Vec<DailyConcurrency>,
+
+    /// Trips in progress at each minute with at least one, sorted by minute
+    pub minutes: Vec<ActiveTrips>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DailyConcurrency {
+    /// Day of the trips
+    pub date: NaiveDate,
+
+    /// Number of minutes of the day with at least one trip in progress
+    pub active_minutes: usize,
+
+    /// Highest number of trips in progress at the same minute
+    pub peak_active_trips: i64,
+
+    /// First minute when the peak was reached
+    #[serde(with = "datetime_format")]
+    pub peak_minute: DateTime<Utc>,
+
+    /// Average number of trips in progress over the minutes of the day
+    pub avg_active_trips: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActiveTrips {
+    /// Start of the minute
+    #[serde(with = "datetime_format")]
+    pub minute: DateTime<Utc>,
+
+    /// Number of trips in progress during the minute
+    pub active_trips: i64,
+}
@@ -0,0 +1,262 @@
+//! Detour analysis module
+//!
+//! Compares the distance reported by the meter with the straight-line (haversine)
+//! distance between pickup and dropoff. Reports the distribution of the ratio between
+//! both per zone pair and hour, and flags trips whose ratio is implausible: meter
+//! distances much shorter than the straight line point to GPS errors, while much
+//! longer ones point to suspicious routing.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use chrono::Timelike;
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+use super::batch_aggregator::BatchAggregator;
+use super::detour_stats::{DetourAnalysis, DetourFlag, DetourStats};
+
+/// Straight-line distances below this many miles give meaningless ratios
+const MIN_STRAIGHT_LINE_MILES: f64 = 0.1;
+
+/// Ratios are stored in the histogram in hundredths, capped at this value
+const MAX_HISTOGRAM_RATIO: i64 = 1_000;
+
+/// Upper bound (exclusive) and label of each range of the ratio distribution
+const RATIO_RANGES: [(f64, &str); 7] = [
+    (0.5, "0.00-0.50"),
+    (1.0, "0.50-1.00"),
+    (1.25, "1.00-1.25"),
+    (1.5, "1.25-1.50"),
+    (2.0, "1.50-2.00"),
+    (3.0, "2.00-3.00"),
+    (f64::INFINITY, "3.00+"),
+];
+
+type DetourKey = (String, String, u32);
+type DetourStatsData = (usize, f64, BTreeMap<i64, usize>, usize);
+type DetourStatsMap = HashMap<DetourKey, DetourStatsData>;
+
+/// Batch aggregator for detour analysis
+#[derive(Debug)]
+pub struct DetourAnalyzer {
+    min_ratio: f64,
+    max_ratio: f64,
+    max_flagged: usize,
+    detour_stats: DetourStatsMap,
+    flagged_trips: Vec<DetourFlag>,
+}
+
+impl DetourAnalyzer {
+    /// Creates an analyzer that flags trips whose ratio is below `min_ratio`
+    /// or above `max_ratio`
+    pub fn with_thresholds(min_ratio: f64, max_ratio: f64) -> Self {
+        Self {
+            min_ratio,
+            max_ratio,
+            max_flagged: crate::config::AnalysisConfig::default().max_flagged_trips,
+            detour_stats: HashMap::new(),
+            flagged_trips: Vec::new(),
+        }
+    }
+
+    /// Lists at most `max_flagged` flagged trips, keeping the earliest ones
+    pub fn with_max_flagged(mut self, max_flagged: usize) -> Self {
+        self.max_flagged = max_flagged;
+        self
+    }
+
+    /// Returns the reason why the ratio is implausible, if it is
+    fn flag_reason(&self, ratio: f64) -> Option<&'static str> {
+        if ratio < self.min_ratio {
+            Some("meter_shorter_than_straight_line")
+        } else if ratio > self.max_ratio {
+            Some("excessive_detour")
+        } else {
+            None
+        }
+    }
+}
+
+/// Order in which flagged trips are listed
+fn flag_order(flag: &DetourFlag) -> (chrono::DateTime<chrono::Utc>, i32) {
+    (flag.pickup_datetime, flag.vendor_id)
+}
+
+impl Default for DetourAnalyzer {
+    fn default() -> Self {
+        let config = crate::config::AnalysisConfig::default();
+        Self::with_thresholds(config.min_detour_ratio, config.max_detour_ratio)
+    }
+}
+
+/// Adds the values of `other` into `entry`
+fn merge_detour_stats(entry: &mut DetourStatsData, other: DetourStatsData) {
+    let (count, ratio_sum, histogram, flagged) = other;
+    entry.0 += count;
+    entry.1 += ratio_sum;
+    for (ratio, ratio_count) in histogram {
+        *entry.2.entry(ratio).or_insert(0) += ratio_count;
+    }
+    entry.3 += flagged;
+}
+
+impl BatchAggregator<DetourAnalysis> for DetourAnalyzer {
+    type Accumulator = (DetourStatsMap, Vec<DetourFlag>);
+
+    /// Process a single batch and accumulate intermediate results
+    /// Filters out trips with invalid coordinates or too short straight-line distances
+    /// Accumulates trip count, sum of ratios, a histogram of ratios and the amount of
+    /// flagged trips per zone pair and hour, keeping the earliest flagged trips aside
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::{get_zone_id, haversine_miles, is_valid_nyc_coordinate, keep_smallest};
+
+        let this = &*self;
+        let batch_acc = batch
+            .par_iter()
+            .filter(|trip| {
+                is_valid_nyc_coordinate(trip.pickup_latitude, trip.pickup_longitude)
+                    && is_valid_nyc_coordinate(trip.dropoff_latitude, trip.dropoff_longitude)
+            })
+            .fold(
+                || (HashMap::new(), Vec::new()),
+                |(mut acc, mut flags), trip| {
+                    let straight_line = haversine_miles(
+                        trip.pickup_latitude,
+                        trip.pickup_longitude,
+                        trip.dropoff_latitude,
+                        trip.dropoff_longitude,
+                    );
+                    if straight_line < MIN_STRAIGHT_LINE_MILES {
+                        return (acc, flags);
+                    }
+
+                    let ratio = trip.trip_distance / straight_line;
+                    let hour = trip.pickup_datetime.hour();
+                    let pickup_zone = get_zone_id(trip.pickup_latitude, trip.pickup_longitude);
+                    let dropoff_zone = get_zone_id(trip.dropoff_latitude, trip.dropoff_longitude);
+                    let reason = this.flag_reason(ratio);
+
+                    let entry = acc
+                        .entry((pickup_zone.clone(), dropoff_zone.clone(), hour))
+                        .or_insert_with(|| (0, 0.0, BTreeMap::new(), 0));
+                    entry.0 += 1; // trip count
+                    entry.1 += ratio; // ratio sum
+                    let bucket = ((ratio * 100.0).round() as i64).min(MAX_HISTOGRAM_RATIO);
+                    *entry.2.entry(bucket).or_insert(0) += 1; // ratio histogram
+
+                    if let Some(reason) = reason {
+                        entry.3 += 1; // flagged count
+                        flags.push(DetourFlag {
+                            pickup_datetime: trip.pickup_datetime,
+                            vendor_id: trip.vendor_id,
+                            pickup_zone,
+                            dropoff_zone,
+                            trip_distance: trip.trip_distance,
+                            straight_line_distance: straight_line,
+                            ratio,
+                            reason: reason.to_string(),
+                        });
+                        keep_smallest(&mut flags, this.max_flagged, flag_order);
+                    }
+                    (acc, flags)
+                },
+            )
+            .reduce(
+                || (HashMap::new(), Vec::new()),
+                |(mut acc1, mut flags1), (acc2, flags2)| {
+                    for (key, stats) in acc2 {
+                        let entry = acc1
+                            .entry(key)
+                            .or_insert_with(|| (0, 0.0, BTreeMap::new(), 0));
+                        merge_detour_stats(entry, stats);
+                    }
+                    flags1.extend(flags2);
+                    keep_smallest(&mut flags1, this.max_flagged, flag_order);
+                    (acc1, flags1)
+                },
+            );
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for (accumulator, flags) in accumulators {
+            for (key, stats) in accumulator {
+                let entry = self
+                    .detour_stats
+                    .entry(key)
+                    .or_insert_with(|| (0, 0.0, BTreeMap::new(), 0));
+                merge_detour_stats(entry, stats);
+            }
+            self.flagged_trips.extend(flags);
+            crate::utils::keep_smallest(&mut self.flagged_trips, self.max_flagged, flag_order);
+        }
+        Ok(())
+    }
+
+    /// Generates the ratio distributions sorted by zone pair and hour, and the earliest
+    /// flagged trips sorted by pickup time and vendor
+    fn finalize(self) -> Result<DetourAnalysis, ProcessingError> {
+        use crate::utils::{quantile_from_histogram, round_to_2_decimals};
+
+        let mut stats: Vec<DetourStats> = self
+            .detour_stats
+            .into_iter()
+            .map(
+                |((pickup_zone, dropoff_zone, hour), (count, ratio_sum, histogram, flagged))| {
+                    let mut ratio_distribution: BTreeMap<String, usize> = RATIO_RANGES
+                        .iter()
+                        .map(|(_, label)| (label.to_string(), 0))
+                        .collect();
+                    for (&bucket, &bucket_count) in &histogram {
+                        let ratio = bucket as f64 / 100.0;
+                        if let Some((_, label)) =
+                            RATIO_RANGES.iter().find(|(upper, _)| ratio < *upper)
+                        {
+                            *ratio_distribution.entry(label.to_string()).or_insert(0) +=
+                                bucket_count;
+                        }
+                    }
+
+                    DetourStats {
+                        pickup_zone,
+                        dropoff_zone,
+                        hour,
+                        trip_count: count,
+                        avg_ratio: round_to_2_decimals(ratio_sum / count as f64),
+                        median_ratio: quantile_from_histogram(&histogram, 0.5).unwrap_or(0) as f64
+                            / 100.0,
+                        p90_ratio: quantile_from_histogram(&histogram, 0.9).unwrap_or(0) as f64
+                            / 100.0,
+                        ratio_distribution,
+                        flagged_count: flagged,
+                    }
+                },
+            )
+            .collect();
+
+        stats.sort_by(|a, b| {
+            (&a.pickup_zone, &a.dropoff_zone, a.hour).cmp(&(
+                &b.pickup_zone,
+                &b.dropoff_zone,
+                b.hour,
+            ))
+        });
+
+        let mut flagged_trips = self.flagged_trips;
+        flagged_trips.sort_by_key(flag_order);
+        flagged_trips.truncate(self.max_flagged);
+        for flag in &mut flagged_trips {
+            flag.straight_line_distance = round_to_2_decimals(flag.straight_line_distance);
+            flag.ratio = round_to_2_decimals(flag.ratio);
+        }
+
+        Ok(DetourAnalysis {
+            stats,
+            flagged_trips,
+        })
+    }
+}
@@ -0,0 +1,73 @@
+//! Defines the structs produced by the detour analysis.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::models::datetime_format;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DetourAnalysis {
+    /// Detour ratio distribution per zone pair and hour
+    pub stats: Vec<DetourStats>,
+
+    /// Earliest trips whose detour ratio is implausible
+    pub flagged_trips: Vec<DetourFlag>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DetourStats {
+    /// Zone where the meter was engaged
+    pub pickup_zone: String,
+
+    /// Zone where the meter was disengaged
+    pub dropoff_zone: String,
+
+    /// Hour of the day (0-23)
+    pub hour: u32,
+
+    /// Number of trips with a measurable straight-line distance
+    pub trip_count: usize,
+
+    /// Average ratio between the meter distance and the straight-line distance
+    pub avg_ratio: f64,
+
+    /// Median detour ratio
+    pub median_ratio: f64,
+
+    /// 90th percentile of the detour ratio
+    pub p90_ratio: f64,
+
+    /// Number of trips per detour ratio range
+    pub ratio_distribution: BTreeMap<String, usize>,
+
+    /// Number of trips flagged as implausible
+    pub flagged_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetourFlag {
+    /// Date and time when the meter was engaged
+    #[serde(with = "datetime_format")]
+    pub pickup_datetime: DateTime<Utc>,
+
+    /// Vendor that provided the record
+    pub vendor_id: i32,
+
+    /// Zone where the meter was engaged
+    pub pickup_zone: String,
+
+    /// Zone where the meter was disengaged
+    pub dropoff_zone: String,
+
+    /// Distance in miles reported by the meter
+    pub trip_distance: f64,
+
+    /// Haversine distance in miles between pickup and dropoff
+    pub straight_line_distance: f64,
+
+    /// Ratio between both distances
+    pub ratio: f64,
+
+    /// Why the ratio is implausible
+    pub reason: String,
+}
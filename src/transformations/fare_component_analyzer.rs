@@ -0,0 +1,134 @@
+//! Fare component analysis module
+//!
+//! Decomposes the revenue of each hour, pickup zone and payment type into fare, extra,
+//! MTA tax, improvement surcharge, tips and tolls, reporting the share of the total
+//! amount that each part represents along with any amount the components do not explain.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use chrono::Timelike;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use super::batch_aggregator::BatchAggregator;
+use super::fare_components::FareComponents;
+
+type ComponentKey = (u32, String, i32);
+type ComponentData = (usize, f64, f64, f64, f64, f64, f64, f64);
+type ComponentMap = HashMap<ComponentKey, ComponentData>;
+
+/// Batch aggregator for fare component analysis
+#[derive(Debug, Default)]
+pub struct FareComponentAnalyzer {
+    component_stats: ComponentMap,
+}
+
+/// Adds the totals of `source` into `target`
+fn merge_data(target: &mut ComponentData, source: ComponentData) {
+    let (count, fare, extra, mta_tax, surcharge, tips, tolls, total) = source;
+    target.0 += count;
+    target.1 += fare;
+    target.2 += extra;
+    target.3 += mta_tax;
+    target.4 += surcharge;
+    target.5 += tips;
+    target.6 += tolls;
+    target.7 += total;
+}
+
+impl BatchAggregator<Vec<FareComponents>> for FareComponentAnalyzer {
+    type Accumulator = ComponentMap;
+
+    /// Process a single batch and accumulate intermediate results
+    /// Filters out trips with non-positive total amounts
+    /// Accumulates trip count and the total of each component and of the amount charged
+    /// per hour, pickup zone and payment type
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::get_zone_id;
+
+        let batch_acc = batch
+            .par_iter()
+            .filter(|trip| trip.total_amount > 0.0)
+            .fold(HashMap::new, |mut acc: ComponentMap, trip| {
+                let key = (
+                    trip.pickup_datetime.hour(),
+                    get_zone_id(trip.pickup_latitude, trip.pickup_longitude),
+                    trip.payment_type,
+                );
+                let entry = acc.entry(key).or_default();
+                entry.0 += 1; // trip count
+                entry.1 += trip.fare_amount;
+                entry.2 += trip.extra;
+                entry.3 += trip.mta_tax;
+                entry.4 += trip.improvement_surcharge.unwrap_or(0.0);
+                entry.5 += trip.tip_amount;
+                entry.6 += trip.tolls_amount;
+                entry.7 += trip.total_amount;
+                acc
+            })
+            .reduce(HashMap::new, |mut acc1, acc2| {
+                for (key, data) in acc2 {
+                    merge_data(acc1.entry(key).or_default(), data);
+                }
+                acc1
+            });
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for accumulator in accumulators {
+            for (key, data) in accumulator {
+                merge_data(self.component_stats.entry(key).or_default(), data);
+            }
+        }
+        Ok(())
+    }
+
+    /// Generates the breakdowns sorted by hour, zone and payment type
+    fn finalize(self) -> Result<Vec<FareComponents>, ProcessingError> {
+        use crate::utils::round_to_2_decimals;
+
+        let mut results: Vec<FareComponents> = self
+            .component_stats
+            .into_iter()
+            .map(
+                |(
+                    (hour, zone_id, payment_type),
+                    (count, fare, extra, mta_tax, surcharge, tips, tolls, total),
+                )| {
+                    let share = |amount: f64| round_to_2_decimals(amount / total * 100.0);
+                    FareComponents {
+                        hour,
+                        zone_id,
+                        payment_type,
+                        trip_count: count,
+                        fare_amount: round_to_2_decimals(fare),
+                        extra: round_to_2_decimals(extra),
+                        mta_tax: round_to_2_decimals(mta_tax),
+                        improvement_surcharge: round_to_2_decimals(surcharge),
+                        tip_amount: round_to_2_decimals(tips),
+                        tolls_amount: round_to_2_decimals(tolls),
+                        total_amount: round_to_2_decimals(total),
+                        unreconciled_amount: round_to_2_decimals(
+                            total - (fare + extra + mta_tax + surcharge + tips + tolls),
+                        ),
+                        fare_share: share(fare),
+                        surcharge_share: share(extra + mta_tax + surcharge),
+                        tip_share: share(tips),
+                        tolls_share: share(tolls),
+                    }
+                },
+            )
+            .collect();
+
+        results.sort_by(|a, b| {
+            (a.hour, &a.zone_id, a.payment_type).cmp(&(b.hour, &b.zone_id, b.payment_type))
+        });
+
+        Ok(results)
+    }
+}
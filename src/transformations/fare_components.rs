@@ -0,0 +1,54 @@
+//! Defines the FareComponents struct used in fare component analysis.
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FareComponents {
+    /// Hour of the day (0-23) when the trip started
+    pub hour: u32,
+
+    /// Pickup zone
+    pub zone_id: String,
+
+    /// Payment type identifier
+    pub payment_type: i32,
+
+    /// Number of trips
+    pub trip_count: usize,
+
+    /// Total time-and-distance fare
+    pub fare_amount: f64,
+
+    /// Total rush hour and overnight surcharges
+    pub extra: f64,
+
+    /// Total MTA tax
+    pub mta_tax: f64,
+
+    /// Total improvement surcharge
+    pub improvement_surcharge: f64,
+
+    /// Total tips
+    pub tip_amount: f64,
+
+    /// Total tolls
+    pub tolls_amount: f64,
+
+    /// Total amount charged
+    pub total_amount: f64,
+
+    /// Part of the total amount not explained by the components
+    pub unreconciled_amount: f64,
+
+    /// Percentage of the total amount that comes from the fare
+    pub fare_share: f64,
+
+    /// Percentage of the total amount that comes from extra, MTA tax and
+    /// improvement surcharge
+    pub surcharge_share: f64,
+
+    /// Percentage of the total amount that comes from tips
+    pub tip_share: f64,
+
+    /// Percentage of the total amount that comes from tolls
+    pub tolls_share: f64,
+}
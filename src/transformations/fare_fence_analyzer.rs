@@ -0,0 +1,89 @@
+//! Fare fence analysis module
+//!
+//! Computes Tukey fences of the fare amount for each pickup zone and hour out of
+//! mergeable quantile sketches. Fares outside `Q1 - k * IQR` and `Q3 + k * IQR` are
+//! considered statistical outliers by the anomaly analysis, which needs the fences
+//! before it sees the trips and therefore runs in a second pass.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use crate::sketches::QuantileSketch;
+use chrono::Timelike;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use super::batch_aggregator::BatchAggregator;
+use super::fare_fences::FareFences;
+
+/// Multiple of the interquartile range between the quartiles and the fences.
+/// Three flags only extreme outliers, long trips make fares naturally skewed
+const IQR_MULTIPLIER: f64 = 3.0;
+
+/// Zone and hour pairs with fewer trips get no fences
+const MIN_TRIPS: u64 = 30;
+
+type FenceMap = HashMap<(String, u32), QuantileSketch>;
+
+/// Batch aggregator for fare fence analysis
+#[derive(Debug, Default)]
+pub struct FareFenceAnalyzer {
+    fare_sketches: FenceMap,
+}
+
+impl BatchAggregator<FareFences> for FareFenceAnalyzer {
+    type Accumulator = FenceMap;
+
+    /// Process a single batch and accumulate intermediate results
+    /// Accumulates a sketch of the fare amounts per pickup zone and hour
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::get_zone_id;
+
+        let batch_acc = batch
+            .par_iter()
+            .fold(HashMap::new, |mut acc: FenceMap, trip| {
+                let key = (
+                    get_zone_id(trip.pickup_latitude, trip.pickup_longitude),
+                    trip.pickup_datetime.hour(),
+                );
+                acc.entry(key).or_default().add(trip.fare_amount);
+                acc
+            })
+            .reduce(HashMap::new, |mut acc1, acc2| {
+                for (key, sketch) in acc2 {
+                    acc1.entry(key).or_default().merge(&sketch);
+                }
+                acc1
+            });
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for accumulator in accumulators {
+            for (key, sketch) in accumulator {
+                self.fare_sketches.entry(key).or_default().merge(&sketch);
+            }
+        }
+        Ok(())
+    }
+
+    /// Computes the fences of the zone and hour pairs with enough trips
+    fn finalize(self) -> Result<FareFences, ProcessingError> {
+        let fences = self
+            .fare_sketches
+            .into_iter()
+            .filter(|(_, sketch)| sketch.count() >= MIN_TRIPS)
+            .filter_map(|(key, sketch)| {
+                let q1 = sketch.quantile(0.25)?;
+                let q3 = sketch.quantile(0.75)?;
+                let iqr = q3 - q1;
+                Some((key, (q1 - IQR_MULTIPLIER * iqr, q3 + IQR_MULTIPLIER * iqr)))
+            })
+            .collect();
+
+        Ok(FareFences { fences })
+    }
+}
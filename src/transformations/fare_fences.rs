@@ -0,0 +1,25 @@
+//! Defines the FareFences struct produced by the fare fence analysis.
+use std::collections::HashMap;
+
+/// Lower and upper limits of a plausible fare amount per pickup zone and hour
+#[derive(Debug, Default, Clone)]
+pub struct FareFences {
+    pub fences: HashMap<(String, u32), (f64, f64)>,
+}
+
+impl FareFences {
+    /// Returns the lower and upper fences of the zone and hour, if it had enough trips
+    pub fn get(&self, zone_id: &str, hour: u32) -> Option<(f64, f64)> {
+        self.fences.get(&(zone_id.to_string(), hour)).copied()
+    }
+
+    /// Number of zone and hour pairs with fences
+    pub fn len(&self) -> usize {
+        self.fences.len()
+    }
+
+    /// Whether no zone and hour pair had enough trips
+    pub fn is_empty(&self) -> bool {
+        self.fences.is_empty()
+    }
+}
@@ -0,0 +1,138 @@
+//! Geohash grid analysis module
+//!
+//! Bins pickups and dropoffs into geohash cells of configurable precision, giving a
+//! much finer view than borough-level zones. Calculates the amount of pickups and
+//! dropoffs, the total revenue and the average fare for each cell and hour, to be
+//! exported as GeoJSON heatmaps.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use chrono::Timelike;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use super::batch_aggregator::BatchAggregator;
+use super::grid_cell::GridCell;
+
+type CellKey = (String, u32);
+type CellStatsData = (usize, usize, f64, f64);
+type CellStatsMap = HashMap<CellKey, CellStatsData>;
+
+/// Batch aggregator for geohash grid analysis
+#[derive(Debug)]
+pub struct GridAnalyzer {
+    precision: usize,
+    cell_stats: CellStatsMap,
+}
+
+impl GridAnalyzer {
+    /// Creates an analyzer that uses geohash cells of `precision` characters
+    pub fn with_precision(precision: usize) -> Self {
+        Self {
+            precision,
+            cell_stats: HashMap::new(),
+        }
+    }
+}
+
+impl Default for GridAnalyzer {
+    fn default() -> Self {
+        Self::with_precision(crate::config::AnalysisConfig::default().grid_precision)
+    }
+}
+
+impl BatchAggregator<Vec<GridCell>> for GridAnalyzer {
+    type Accumulator = CellStatsMap;
+
+    /// Process a single batch and accumulate intermediate results
+    /// Pickups and dropoffs outside the NYC area are ignored independently
+    /// Accumulates pickup count, dropoff count, total revenue and total fare per cell
+    /// and hour, attributing revenue and fare to the pickup cell and hour and each
+    /// dropoff to the hour when the trip ended
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::{encode_geohash, is_valid_nyc_coordinate};
+
+        let precision = self.precision;
+        let batch_stats: CellStatsMap = batch
+            .par_iter()
+            .filter(|trip| trip.total_amount > 0.0)
+            .fold(HashMap::new, |mut acc, trip| {
+                if is_valid_nyc_coordinate(trip.pickup_latitude, trip.pickup_longitude) {
+                    let cell =
+                        encode_geohash(trip.pickup_latitude, trip.pickup_longitude, precision);
+                    let entry = acc
+                        .entry((cell, trip.pickup_datetime.hour()))
+                        .or_insert((0, 0, 0.0, 0.0));
+                    entry.0 += 1; // pickup count
+                    entry.2 += trip.total_amount; // total revenue
+                    entry.3 += trip.fare_amount; // total fare
+                }
+
+                if is_valid_nyc_coordinate(trip.dropoff_latitude, trip.dropoff_longitude) {
+                    let cell =
+                        encode_geohash(trip.dropoff_latitude, trip.dropoff_longitude, precision);
+                    let entry = acc
+                        .entry((cell, trip.dropoff_datetime.hour()))
+                        .or_insert((0, 0, 0.0, 0.0));
+                    entry.1 += 1; // dropoff count
+                }
+                acc
+            })
+            .reduce(HashMap::new, |mut acc1, acc2| {
+                for (key, (pickups, dropoffs, revenue, fare)) in acc2 {
+                    let entry = acc1.entry(key).or_insert((0, 0, 0.0, 0.0));
+                    entry.0 += pickups;
+                    entry.1 += dropoffs;
+                    entry.2 += revenue;
+                    entry.3 += fare;
+                }
+                acc1
+            });
+
+        Ok(batch_stats)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for accumulator in accumulators {
+            for (key, (pickups, dropoffs, revenue, fare)) in accumulator {
+                let entry = self.cell_stats.entry(key).or_insert((0, 0, 0.0, 0.0));
+                entry.0 += pickups;
+                entry.1 += dropoffs;
+                entry.2 += revenue;
+                entry.3 += fare;
+            }
+        }
+        Ok(())
+    }
+
+    /// Generates the cells sorted by geohash and hour
+    fn finalize(self) -> Result<Vec<GridCell>, ProcessingError> {
+        use crate::utils::round_to_2_decimals;
+
+        let mut cells: Vec<GridCell> = self
+            .cell_stats
+            .into_iter()
+            .map(
+                |((geohash, hour), (pickups, dropoffs, revenue, fare))| GridCell {
+                    geohash,
+                    hour,
+                    pickup_count: pickups,
+                    dropoff_count: dropoffs,
+                    total_revenue: round_to_2_decimals(revenue),
+                    avg_fare: if pickups > 0 {
+                        round_to_2_decimals(fare / pickups as f64)
+                    } else {
+                        0.0
+                    },
+                },
+            )
+            .collect();
+
+        cells.sort_by(|a, b| (&a.geohash, a.hour).cmp(&(&b.geohash, b.hour)));
+
+        Ok(cells)
+    }
+}
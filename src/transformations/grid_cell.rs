@@ -0,0 +1,35 @@
+//! Defines the GridCell struct used in geohash grid analysis.
+use serde::{Deserialize, Serialize};
+
+use crate::error::ProcessingError;
+use crate::models::{Feature, Geometry};
+use crate::utils::geohash_bounds;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GridCell {
+    /// Geohash identifying the cell
+    pub geohash: String,
+
+    /// Hour of the day (0-23)
+    pub hour: u32,
+
+    /// Number of trips that started in this cell during the specified hour
+    pub pickup_count: usize,
+
+    /// Number of trips that ended in this cell during the specified hour
+    pub dropoff_count: usize,
+
+    /// Total revenue of the trips that started in this cell
+    pub total_revenue: f64,
+
+    /// Average fare amount of the trips that started in this cell
+    pub avg_fare: f64,
+}
+
+impl GridCell {
+    /// Builds a GeoJSON feature with the cell polygon and its stats as properties
+    pub fn to_feature(&self) -> Result<Feature, ProcessingError> {
+        let bounds = geohash_bounds(&self.geohash)?;
+        Ok(Feature::new(Geometry::rectangle(&bounds), self)?)
+    }
+}
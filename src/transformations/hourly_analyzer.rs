@@ -1,11 +1,13 @@
 //! Batch aggregator for hourly pattern analysis of taxi trips
 //!
-//! Calculates the amount of tips, average trip distance,
-//! fare, and tip percentage per hour of the day,
+//! Calculates the amount of tips, average, percentiles, standard deviation, minimum
+//! and maximum of trip distance, fare and duration, and tip percentage per hour of the day,
 //! making the distinction between weekends and weekdays.
 //! Also indicates if the hour being analyzed is a peak hour.
 use crate::error::ProcessingError;
 use crate::models::TaxiTrip;
+use crate::sketches::{Moments, QuantileSketch};
+use crate::utils::{get_day_of_week, is_peak_hour};
 use chrono::Timelike;
 use rayon::prelude::*;
 use std::collections::HashMap;
@@ -13,8 +15,62 @@ use std::collections::HashMap;
 use super::batch_aggregator::BatchAggregator;
 use super::hourly_pattern::HourlyPattern;
 
-type HourlyStatsData = (usize, f64, f64, f64);
-type HourlyStatsMap = HashMap<u32, HourlyStatsData>;
+type HourlyKey = (&'static str, u32);
+type HourlyStatsData = (
+    usize,
+    f64,
+    f64,
+    f64,
+    f64,
+    QuantileSketch,
+    QuantileSketch,
+    QuantileSketch,
+    Moments,
+    Moments,
+    Moments,
+    Moments,
+);
+type HourlyStatsMap = HashMap<HourlyKey, HourlyStatsData>;
+
+/// Returns whether the trip started on a "weekday" or on the "weekend"
+fn day_type(trip: &TaxiTrip) -> Result<&'static str, ProcessingError> {
+    // Days are numbered from Monday (0) to Sunday (6)
+    Ok(if get_day_of_week(trip)? >= 5 {
+        "weekend"
+    } else {
+        "weekday"
+    })
+}
+
+/// Adds the totals and sketches of `source` into `target`
+fn merge_data(target: &mut HourlyStatsData, source: HourlyStatsData) {
+    let (
+        count,
+        distance,
+        fare,
+        duration,
+        tips,
+        distances,
+        fares,
+        durations,
+        distance_moments,
+        fare_moments,
+        duration_moments,
+        tip_moments,
+    ) = source;
+    target.0 += count;
+    target.1 += distance;
+    target.2 += fare;
+    target.3 += duration;
+    target.4 += tips;
+    target.5.merge(&distances);
+    target.6.merge(&fares);
+    target.7.merge(&durations);
+    target.8.merge(&distance_moments);
+    target.9.merge(&fare_moments);
+    target.10.merge(&duration_moments);
+    target.11.merge(&tip_moments);
+}
 
 /// Batch aggregator for hourly pattern analysis
 #[derive(Debug, Default)]
@@ -27,31 +83,37 @@ impl BatchAggregator<Vec<HourlyPattern>> for HourlyPatternAnalyzer {
 
     // Process a single batch and accumulate intermediate results
     // Filters out trips with non-positive total amounts or distances
-    // Accumulates trip count, total distance, total fare, and total duration per hour
+    // Accumulates trip count, total distance, total fare, total duration, total tips,
+    // sketches of distances, fares and durations, and moments of distances, fares,
+    // durations and tips per day type and hour
     fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
         let batch_stats: HourlyStatsMap = batch
             .par_iter()
             .filter(|trip| trip.total_amount > 0.0 && trip.trip_distance > 0.0)
-            .fold(HashMap::new, |mut acc, trip| {
-                let hour = trip.pickup_datetime.hour();
+            .try_fold(HashMap::new, |mut acc: HourlyStatsMap, trip| {
+                let key = (day_type(trip)?, trip.pickup_datetime.hour());
                 let duration = (trip.dropoff_datetime - trip.pickup_datetime).num_minutes() as f64;
-                let entry = acc.entry(hour).or_insert((0, 0.0, 0.0, 0.0));
+                let entry = acc.entry(key).or_default();
                 entry.0 += 1;
                 entry.1 += trip.trip_distance;
                 entry.2 += trip.fare_amount;
                 entry.3 += duration;
-                acc
+                entry.4 += trip.tip_amount;
+                entry.5.add(trip.trip_distance);
+                entry.6.add(trip.fare_amount);
+                entry.7.add(duration);
+                entry.8.add(trip.trip_distance);
+                entry.9.add(trip.fare_amount);
+                entry.10.add(duration);
+                entry.11.add(trip.tip_amount);
+                Ok::<_, ProcessingError>(acc)
             })
-            .reduce(HashMap::new, |mut acc1, acc2| {
-                for (hour, (count, distance, fare, duration)) in acc2 {
-                    let entry = acc1.entry(hour).or_insert((0, 0.0, 0.0, 0.0));
-                    entry.0 += count;
-                    entry.1 += distance;
-                    entry.2 += fare;
-                    entry.3 += duration;
+            .try_reduce(HashMap::new, |mut acc1, acc2| {
+                for (key, data) in acc2 {
+                    merge_data(acc1.entry(key).or_default(), data);
                 }
-                acc1
-            });
+                Ok(acc1)
+            })?;
 
         Ok(batch_stats)
     }
@@ -62,12 +124,8 @@ impl BatchAggregator<Vec<HourlyPattern>> for HourlyPatternAnalyzer {
         accumulators: Vec<Self::Accumulator>,
     ) -> Result<(), ProcessingError> {
         for accumulator in accumulators {
-            for (hour, (count, distance, fare, duration)) in accumulator {
-                let entry = self.hourly_stats.entry(hour).or_insert((0, 0.0, 0.0, 0.0));
-                entry.0 += count;
-                entry.1 += distance;
-                entry.2 += fare;
-                entry.3 += duration;
+            for (key, data) in accumulator {
+                merge_data(self.hourly_stats.entry(key).or_default(), data);
             }
         }
         Ok(())
@@ -81,17 +139,49 @@ impl BatchAggregator<Vec<HourlyPattern>> for HourlyPatternAnalyzer {
             .hourly_stats
             .into_iter()
             .map(
-                |(hour, (count, total_distance, total_fare, total_duration))| HourlyPattern {
+                |(
+                    (day_type, hour),
+                    (
+                        count,
+                        total_distance,
+                        total_fare,
+                        total_duration,
+                        total_tips,
+                        distances,
+                        fares,
+                        durations,
+                        distance_moments,
+                        fare_moments,
+                        duration_moments,
+                        tip_moments,
+                    ),
+                )| HourlyPattern {
+                    day_type: day_type.to_string(),
                     hour,
+                    is_peak_hour: day_type == "weekday" && is_peak_hour(hour as u8),
                     trip_count: count,
                     avg_distance: round_to_2_decimals(total_distance / count as f64),
                     avg_fare: round_to_2_decimals(total_fare / count as f64),
                     avg_duration: round_to_2_decimals(total_duration / count as f64),
+                    distance_percentiles: distances.percentiles(),
+                    fare_percentiles: fares.percentiles(),
+                    duration_percentiles: durations.percentiles(),
+                    distance_spread: distance_moments.spread(),
+                    fare_spread: fare_moments.spread(),
+                    duration_spread: duration_moments.spread(),
+                    tip_spread: tip_moments.spread(),
+                    total_tips: round_to_2_decimals(total_tips),
+                    avg_tip: round_to_2_decimals(total_tips / count as f64),
+                    tip_percentage: if total_fare > 0.0 {
+                        round_to_2_decimals(total_tips / total_fare * 100.0)
+                    } else {
+                        0.0
+                    },
                 },
             )
             .collect();
 
-        hourly_patterns.sort_by_key(|h| h.hour);
+        hourly_patterns.sort_by(|a, b| (&a.day_type, a.hour).cmp(&(&b.day_type, b.hour)));
         Ok(hourly_patterns)
     }
 }
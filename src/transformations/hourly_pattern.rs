@@ -1,10 +1,33 @@
+use crate::sketches::{Percentiles, Spread};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HourlyPattern {
+    /// Either "weekday" or "weekend"
+    pub day_type: String,
     pub hour: u32,
+    /// Whether the hour is a peak traffic hour (only on weekdays)
+    pub is_peak_hour: bool,
     pub trip_count: usize,
     pub avg_distance: f64,
     pub avg_fare: f64,
     pub avg_duration: f64,
+    /// Median, 90th and 99th percentiles of the trip distance in miles
+    pub distance_percentiles: Percentiles,
+    /// Median, 90th and 99th percentiles of the fare amount
+    pub fare_percentiles: Percentiles,
+    /// Median, 90th and 99th percentiles of the trip duration in minutes
+    pub duration_percentiles: Percentiles,
+    /// Standard deviation, minimum and maximum of the trip distance in miles
+    pub distance_spread: Spread,
+    /// Standard deviation, minimum and maximum of the fare amount
+    pub fare_spread: Spread,
+    /// Standard deviation, minimum and maximum of the trip duration in minutes
+    pub duration_spread: Spread,
+    /// Standard deviation, minimum and maximum of the tip amount
+    pub tip_spread: Spread,
+    pub total_tips: f64,
+    pub avg_tip: f64,
+    /// Tips as a percentage of the fare amount
+    pub tip_percentage: f64,
 }
@@ -1,17 +1,97 @@
+pub mod airport_analyzer;
+pub mod airport_stats;
+pub mod anomaly_analyzer;
+pub mod anomaly_stats;
 pub mod batch_aggregator;
+pub mod concurrency_analyzer;
+pub mod concurrency_stats;
+pub mod detour_analyzer;
+pub mod detour_stats;
+pub mod fare_component_analyzer;
+pub mod fare_components;
+pub mod fare_fence_analyzer;
+pub mod fare_fences;
+pub mod grid_analyzer;
+pub mod grid_cell;
 pub mod hourly_analyzer;
 pub mod hourly_pattern;
 pub mod multi_analyzer;
+pub mod net_flow;
+pub mod net_flow_analyzer;
+pub mod occupancy_analyzer;
+pub mod occupancy_stats;
+pub mod od_matrix;
+pub mod od_matrix_analyzer;
 pub mod payment_analyzer;
 pub mod payment_stats;
 pub mod peak_zone;
 pub mod peak_zone_analyzer;
+pub mod poi_activity;
+pub mod poi_analyzer;
+pub mod rate_code_analyzer;
+pub mod rate_code_stats;
+pub mod route_analyzer;
+pub mod speed_analyzer;
+pub mod speed_stats;
+pub mod store_forward_analyzer;
+pub mod store_forward_stats;
+pub mod tariff_analyzer;
+pub mod tariff_stats;
+pub mod time_series;
+pub mod time_series_analyzer;
+pub mod tip_analyzer;
+pub mod tip_stats;
+pub mod top_route;
+pub mod vendor_analyzer;
+pub mod vendor_stats;
+pub mod weekday_hour;
+pub mod weekday_hour_analyzer;
 
+pub use airport_analyzer::AirportAnalyzer;
+pub use airport_stats::{AirportAnalysis, AirportTraffic, FlatFareViolation};
+pub use anomaly_analyzer::AnomalyAnalyzer;
+pub use anomaly_stats::{AnomalyAnalysis, AnomalyFlag, AnomalySummary};
 pub use batch_aggregator::BatchAggregator;
+pub use concurrency_analyzer::ConcurrencyAnalyzer;
+pub use concurrency_stats::{ActiveTrips, ConcurrencyAnalysis, DailyConcurrency};
+pub use detour_analyzer::DetourAnalyzer;
+pub use detour_stats::{DetourAnalysis, DetourFlag, DetourStats};
+pub use fare_component_analyzer::FareComponentAnalyzer;
+pub use fare_components::FareComponents;
+pub use fare_fence_analyzer::FareFenceAnalyzer;
+pub use fare_fences::FareFences;
+pub use grid_analyzer::GridAnalyzer;
+pub use grid_cell::GridCell;
 pub use hourly_analyzer::HourlyPatternAnalyzer;
 pub use hourly_pattern::HourlyPattern;
 pub use multi_analyzer::{MultiAnalysisResults, MultiAnalyzer};
+pub use net_flow::{HourlyNetFlow, ZoneNetFlow};
+pub use net_flow_analyzer::NetFlowAnalyzer;
+pub use occupancy_analyzer::OccupancyAnalyzer;
+pub use occupancy_stats::{OccupancyAnalysis, OccupancyStats, ZoneHourOccupancy};
+pub use od_matrix::OdCell;
+pub use od_matrix_analyzer::OdMatrixAnalyzer;
 pub use payment_analyzer::PaymentAnalyzer;
 pub use payment_stats::PaymentStats;
 pub use peak_zone::PeakZone;
 pub use peak_zone_analyzer::PeakZoneAnalyzer;
+pub use poi_activity::PoiActivity;
+pub use poi_analyzer::PoiAnalyzer;
+pub use rate_code_analyzer::RateCodeAnalyzer;
+pub use rate_code_stats::{NegotiatedFareUsage, RateCodeAnalysis, RateCodeStats};
+pub use route_analyzer::RouteAnalyzer;
+pub use speed_analyzer::SpeedAnalyzer;
+pub use speed_stats::SpeedStats;
+pub use store_forward_analyzer::StoreForwardAnalyzer;
+pub use store_forward_stats::StoreForwardStats;
+pub use tariff_analyzer::TariffAnalyzer;
+pub use tariff_stats::TariffDiscrepancy;
+pub use time_series::{DailyPoint, IntervalPoint, TimeSeries};
+pub use time_series_analyzer::TimeSeriesAnalyzer;
+pub use tip_analyzer::TipAnalyzer;
+pub use tip_stats::{CashTrips, TipAnalysis, TipGroup, TipStats};
+pub use top_route::{RouteCount, TopRoutes};
+pub use vendor_analyzer::VendorAnalyzer;
+pub use vendor_stats::{VendorAnalysis, VendorDayStats, VendorHourStats, VendorMetrics};
+pub use weekday_hour::WeekdayHourStats;
+pub use weekday_hour_analyzer::WeekdayHourAnalyzer;
@@ -1,61 +1,189 @@
 //! Multi-transformation analyzer that runs all transformations in a single pass
+use crate::config::AnalysisConfig;
 use crate::error::ProcessingError;
 use crate::models::TaxiTrip;
 use crate::transformations::{
-    BatchAggregator, HourlyPattern, HourlyPatternAnalyzer, PaymentAnalyzer, PaymentStats, PeakZone,
-    PeakZoneAnalyzer,
+    AirportAnalysis, AirportAnalyzer, AnomalyAnalysis, AnomalyAnalyzer, BatchAggregator,
+    ConcurrencyAnalysis, ConcurrencyAnalyzer, DetourAnalysis, DetourAnalyzer,
+    FareComponentAnalyzer, FareComponents, FareFences, GridAnalyzer, GridCell, HourlyPattern,
+    HourlyPatternAnalyzer, NetFlowAnalyzer, OccupancyAnalysis, OccupancyAnalyzer, OdCell,
+    OdMatrixAnalyzer, PaymentAnalyzer, PaymentStats, PeakZone, PeakZoneAnalyzer, PoiActivity,
+    PoiAnalyzer, RateCodeAnalysis, RateCodeAnalyzer, RouteAnalyzer, SpeedAnalyzer, SpeedStats,
+    StoreForwardAnalyzer, StoreForwardStats, TariffAnalyzer, TariffDiscrepancy, TimeSeries,
+    TimeSeriesAnalyzer, TipAnalysis, TipAnalyzer, TopRoutes, VendorAnalysis, VendorAnalyzer,
+    WeekdayHourAnalyzer, WeekdayHourStats, ZoneNetFlow,
 };
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
-/// Combined results from all three transformations
+/// Combined results from all transformations
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MultiAnalysisResults {
     pub peak_zones: Vec<PeakZone>,
     pub hourly_patterns: Vec<HourlyPattern>,
     pub payment_analysis: Vec<PaymentStats>,
+    pub od_matrix: Vec<OdCell>,
+    pub grid_cells: Vec<GridCell>,
+    pub detour: DetourAnalysis,
+    pub airports: AirportAnalysis,
+    pub poi_activity: Vec<PoiActivity>,
+    pub weekday_hour: Vec<WeekdayHourStats>,
+    pub vendors: VendorAnalysis,
+    pub rate_codes: RateCodeAnalysis,
+    pub occupancy: OccupancyAnalysis,
+    pub speeds: Vec<SpeedStats>,
+    pub top_routes: TopRoutes,
+    pub time_series: TimeSeries,
+    pub fare_components: Vec<FareComponents>,
+    pub anomalies: AnomalyAnalysis,
+    pub tariff_discrepancies: Vec<TariffDiscrepancy>,
+    pub tips: TipAnalysis,
+    pub store_forward: Vec<StoreForwardStats>,
+    pub concurrency: ConcurrencyAnalysis,
+    pub net_flows: Vec<ZoneNetFlow>,
 }
 
-/// Accumulator for all three transformations
-#[derive(Debug)]
+/// Accumulator for all transformations
+#[derive(Debug, Default)]
 pub struct MultiAccumulator {
     pub peak_zones_acc: <PeakZoneAnalyzer as BatchAggregator<Vec<PeakZone>>>::Accumulator,
     pub hourly_patterns_acc:
         <HourlyPatternAnalyzer as BatchAggregator<Vec<HourlyPattern>>>::Accumulator,
     pub payment_analysis_acc: <PaymentAnalyzer as BatchAggregator<Vec<PaymentStats>>>::Accumulator,
+    pub od_matrix_acc: <OdMatrixAnalyzer as BatchAggregator<Vec<OdCell>>>::Accumulator,
+    pub grid_cells_acc: <GridAnalyzer as BatchAggregator<Vec<GridCell>>>::Accumulator,
+    pub detour_acc: <DetourAnalyzer as BatchAggregator<DetourAnalysis>>::Accumulator,
+    pub airports_acc: <AirportAnalyzer as BatchAggregator<AirportAnalysis>>::Accumulator,
+    pub poi_activity_acc: <PoiAnalyzer as BatchAggregator<Vec<PoiActivity>>>::Accumulator,
+    pub weekday_hour_acc:
+        <WeekdayHourAnalyzer as BatchAggregator<Vec<WeekdayHourStats>>>::Accumulator,
+    pub vendors_acc: <VendorAnalyzer as BatchAggregator<VendorAnalysis>>::Accumulator,
+    pub rate_codes_acc: <RateCodeAnalyzer as BatchAggregator<RateCodeAnalysis>>::Accumulator,
+    pub occupancy_acc: <OccupancyAnalyzer as BatchAggregator<OccupancyAnalysis>>::Accumulator,
+    pub speeds_acc: <SpeedAnalyzer as BatchAggregator<Vec<SpeedStats>>>::Accumulator,
+    pub top_routes_acc: <RouteAnalyzer as BatchAggregator<TopRoutes>>::Accumulator,
+    pub time_series_acc: <TimeSeriesAnalyzer as BatchAggregator<TimeSeries>>::Accumulator,
+    pub fare_components_acc:
+        <FareComponentAnalyzer as BatchAggregator<Vec<FareComponents>>>::Accumulator,
+    pub anomalies_acc: <AnomalyAnalyzer as BatchAggregator<AnomalyAnalysis>>::Accumulator,
+    pub tariff_discrepancies_acc:
+        <TariffAnalyzer as BatchAggregator<Vec<TariffDiscrepancy>>>::Accumulator,
+    pub tips_acc: <TipAnalyzer as BatchAggregator<TipAnalysis>>::Accumulator,
+    pub store_forward_acc:
+        <StoreForwardAnalyzer as BatchAggregator<Vec<StoreForwardStats>>>::Accumulator,
+    pub concurrency_acc: <ConcurrencyAnalyzer as BatchAggregator<ConcurrencyAnalysis>>::Accumulator,
+    pub net_flows_acc: <NetFlowAnalyzer as BatchAggregator<Vec<ZoneNetFlow>>>::Accumulator,
 }
 
-impl Default for MultiAccumulator {
-    fn default() -> Self {
-        use std::collections::HashMap;
-        Self {
-            peak_zones_acc: HashMap::new(),
-            hourly_patterns_acc: HashMap::new(),
-            payment_analysis_acc: (HashMap::new(), 0),
-        }
-    }
-}
-
-/// Processes all three transformations in a single pass
+/// Processes all transformations in a single pass
 #[derive(Debug, Default)]
 pub struct MultiAnalyzer {
     peak_zone_analyzer: PeakZoneAnalyzer,
     hourly_pattern_analyzer: HourlyPatternAnalyzer,
     payment_analyzer: PaymentAnalyzer,
+    od_matrix_analyzer: OdMatrixAnalyzer,
+    grid_analyzer: GridAnalyzer,
+    detour_analyzer: DetourAnalyzer,
+    airport_analyzer: AirportAnalyzer,
+    poi_analyzer: PoiAnalyzer,
+    weekday_hour_analyzer: WeekdayHourAnalyzer,
+    vendor_analyzer: VendorAnalyzer,
+    rate_code_analyzer: RateCodeAnalyzer,
+    occupancy_analyzer: OccupancyAnalyzer,
+    speed_analyzer: SpeedAnalyzer,
+    route_analyzer: RouteAnalyzer,
+    time_series_analyzer: TimeSeriesAnalyzer,
+    fare_component_analyzer: FareComponentAnalyzer,
+    anomaly_analyzer: AnomalyAnalyzer,
+    tariff_analyzer: TariffAnalyzer,
+    tip_analyzer: TipAnalyzer,
+    store_forward_analyzer: StoreForwardAnalyzer,
+    concurrency_analyzer: ConcurrencyAnalyzer,
+    net_flow_analyzer: NetFlowAnalyzer,
+}
+
+impl MultiAnalyzer {
+    /// Creates the analyzers using the given configuration
+    pub fn with_config(config: &AnalysisConfig) -> Self {
+        Self {
+            peak_zone_analyzer: PeakZoneAnalyzer::with_cell_precision(config.grid_precision),
+            grid_analyzer: GridAnalyzer::with_precision(config.grid_precision),
+            detour_analyzer: DetourAnalyzer::with_thresholds(
+                config.min_detour_ratio,
+                config.max_detour_ratio,
+            )
+            .with_max_flagged(config.max_flagged_trips),
+            speed_analyzer: SpeedAnalyzer::with_max_speed(config.max_speed_mph),
+            route_analyzer: RouteAnalyzer::new(config.grid_precision, config.top_routes),
+            time_series_analyzer: TimeSeriesAnalyzer::with_quarter_hours(
+                config.quarter_hour_series,
+            ),
+            airport_analyzer: AirportAnalyzer::default().with_max_flagged(config.max_flagged_trips),
+            anomaly_analyzer: AnomalyAnalyzer::default().with_max_flagged(config.max_flagged_trips),
+            tariff_analyzer: TariffAnalyzer::with_schedule(config.tariffs.clone()),
+            ..Self::default()
+        }
+    }
+
+    /// Flags fares outside the given fences as outliers in the anomaly analysis
+    pub fn with_fare_fences(mut self, fences: Option<Arc<FareFences>>) -> Self {
+        self.anomaly_analyzer = AnomalyAnalyzer::with_fences(fences)
+            .with_max_flagged(self.anomaly_analyzer.max_flagged());
+        self
+    }
 }
 
 impl BatchAggregator<MultiAnalysisResults> for MultiAnalyzer {
     type Accumulator = MultiAccumulator;
 
     fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
-        // Process the same batch through all three analyzers
+        // Process the same batch through all analyzers
         let peak_zones_acc = self.peak_zone_analyzer.process_batch(batch)?;
         let hourly_patterns_acc = self.hourly_pattern_analyzer.process_batch(batch)?;
         let payment_analysis_acc = self.payment_analyzer.process_batch(batch)?;
+        let od_matrix_acc = self.od_matrix_analyzer.process_batch(batch)?;
+        let grid_cells_acc = self.grid_analyzer.process_batch(batch)?;
+        let detour_acc = self.detour_analyzer.process_batch(batch)?;
+        let airports_acc = self.airport_analyzer.process_batch(batch)?;
+        let poi_activity_acc = self.poi_analyzer.process_batch(batch)?;
+        let weekday_hour_acc = self.weekday_hour_analyzer.process_batch(batch)?;
+        let vendors_acc = self.vendor_analyzer.process_batch(batch)?;
+        let rate_codes_acc = self.rate_code_analyzer.process_batch(batch)?;
+        let occupancy_acc = self.occupancy_analyzer.process_batch(batch)?;
+        let speeds_acc = self.speed_analyzer.process_batch(batch)?;
+        let top_routes_acc = self.route_analyzer.process_batch(batch)?;
+        let time_series_acc = self.time_series_analyzer.process_batch(batch)?;
+        let fare_components_acc = self.fare_component_analyzer.process_batch(batch)?;
+        let anomalies_acc = self.anomaly_analyzer.process_batch(batch)?;
+        let tariff_discrepancies_acc = self.tariff_analyzer.process_batch(batch)?;
+        let tips_acc = self.tip_analyzer.process_batch(batch)?;
+        let store_forward_acc = self.store_forward_analyzer.process_batch(batch)?;
+        let concurrency_acc = self.concurrency_analyzer.process_batch(batch)?;
+        let net_flows_acc = self.net_flow_analyzer.process_batch(batch)?;
 
         Ok(MultiAccumulator {
             peak_zones_acc,
             hourly_patterns_acc,
             payment_analysis_acc,
+            od_matrix_acc,
+            grid_cells_acc,
+            detour_acc,
+            airports_acc,
+            poi_activity_acc,
+            weekday_hour_acc,
+            vendors_acc,
+            rate_codes_acc,
+            occupancy_acc,
+            speeds_acc,
+            top_routes_acc,
+            time_series_acc,
+            fare_components_acc,
+            anomalies_acc,
+            tariff_discrepancies_acc,
+            tips_acc,
+            store_forward_acc,
+            concurrency_acc,
+            net_flows_acc,
         })
     }
 
@@ -67,11 +195,49 @@ impl BatchAggregator<MultiAnalysisResults> for MultiAnalyzer {
         let mut peak_zones_accs = Vec::new();
         let mut hourly_patterns_accs = Vec::new();
         let mut payment_analysis_accs = Vec::new();
+        let mut od_matrix_accs = Vec::new();
+        let mut grid_cells_accs = Vec::new();
+        let mut detour_accs = Vec::new();
+        let mut airports_accs = Vec::new();
+        let mut poi_activity_accs = Vec::new();
+        let mut weekday_hour_accs = Vec::new();
+        let mut vendors_accs = Vec::new();
+        let mut rate_codes_accs = Vec::new();
+        let mut occupancy_accs = Vec::new();
+        let mut speeds_accs = Vec::new();
+        let mut top_routes_accs = Vec::new();
+        let mut time_series_accs = Vec::new();
+        let mut fare_components_accs = Vec::new();
+        let mut anomalies_accs = Vec::new();
+        let mut tariff_discrepancies_accs = Vec::new();
+        let mut tips_accs = Vec::new();
+        let mut store_forward_accs = Vec::new();
+        let mut concurrency_accs = Vec::new();
+        let mut net_flows_accs = Vec::new();
 
         for acc in accumulators {
             peak_zones_accs.push(acc.peak_zones_acc);
             hourly_patterns_accs.push(acc.hourly_patterns_acc);
             payment_analysis_accs.push(acc.payment_analysis_acc);
+            od_matrix_accs.push(acc.od_matrix_acc);
+            grid_cells_accs.push(acc.grid_cells_acc);
+            detour_accs.push(acc.detour_acc);
+            airports_accs.push(acc.airports_acc);
+            poi_activity_accs.push(acc.poi_activity_acc);
+            weekday_hour_accs.push(acc.weekday_hour_acc);
+            vendors_accs.push(acc.vendors_acc);
+            rate_codes_accs.push(acc.rate_codes_acc);
+            occupancy_accs.push(acc.occupancy_acc);
+            speeds_accs.push(acc.speeds_acc);
+            top_routes_accs.push(acc.top_routes_acc);
+            time_series_accs.push(acc.time_series_acc);
+            fare_components_accs.push(acc.fare_components_acc);
+            anomalies_accs.push(acc.anomalies_acc);
+            tariff_discrepancies_accs.push(acc.tariff_discrepancies_acc);
+            tips_accs.push(acc.tips_acc);
+            store_forward_accs.push(acc.store_forward_acc);
+            concurrency_accs.push(acc.concurrency_acc);
+            net_flows_accs.push(acc.net_flows_acc);
         }
 
         // Merge each transformation's accumulators
@@ -81,20 +247,84 @@ impl BatchAggregator<MultiAnalysisResults> for MultiAnalyzer {
             .merge_accumulators(hourly_patterns_accs)?;
         self.payment_analyzer
             .merge_accumulators(payment_analysis_accs)?;
+        self.od_matrix_analyzer.merge_accumulators(od_matrix_accs)?;
+        self.grid_analyzer.merge_accumulators(grid_cells_accs)?;
+        self.detour_analyzer.merge_accumulators(detour_accs)?;
+        self.airport_analyzer.merge_accumulators(airports_accs)?;
+        self.poi_analyzer.merge_accumulators(poi_activity_accs)?;
+        self.weekday_hour_analyzer
+            .merge_accumulators(weekday_hour_accs)?;
+        self.vendor_analyzer.merge_accumulators(vendors_accs)?;
+        self.rate_code_analyzer
+            .merge_accumulators(rate_codes_accs)?;
+        self.occupancy_analyzer.merge_accumulators(occupancy_accs)?;
+        self.speed_analyzer.merge_accumulators(speeds_accs)?;
+        self.route_analyzer.merge_accumulators(top_routes_accs)?;
+        self.time_series_analyzer
+            .merge_accumulators(time_series_accs)?;
+        self.fare_component_analyzer
+            .merge_accumulators(fare_components_accs)?;
+        self.anomaly_analyzer.merge_accumulators(anomalies_accs)?;
+        self.tariff_analyzer
+            .merge_accumulators(tariff_discrepancies_accs)?;
+        self.tip_analyzer.merge_accumulators(tips_accs)?;
+        self.store_forward_analyzer
+            .merge_accumulators(store_forward_accs)?;
+        self.concurrency_analyzer
+            .merge_accumulators(concurrency_accs)?;
+        self.net_flow_analyzer.merge_accumulators(net_flows_accs)?;
 
         Ok(())
     }
 
     fn finalize(self) -> Result<MultiAnalysisResults, ProcessingError> {
-        // Finalize all three transformations
+        // Finalize all transformations
         let peak_zones = self.peak_zone_analyzer.finalize()?;
         let hourly_patterns = self.hourly_pattern_analyzer.finalize()?;
         let payment_analysis = self.payment_analyzer.finalize()?;
+        let od_matrix = self.od_matrix_analyzer.finalize()?;
+        let grid_cells = self.grid_analyzer.finalize()?;
+        let detour = self.detour_analyzer.finalize()?;
+        let airports = self.airport_analyzer.finalize()?;
+        let poi_activity = self.poi_analyzer.finalize()?;
+        let weekday_hour = self.weekday_hour_analyzer.finalize()?;
+        let vendors = self.vendor_analyzer.finalize()?;
+        let rate_codes = self.rate_code_analyzer.finalize()?;
+        let occupancy = self.occupancy_analyzer.finalize()?;
+        let speeds = self.speed_analyzer.finalize()?;
+        let top_routes = self.route_analyzer.finalize()?;
+        let time_series = self.time_series_analyzer.finalize()?;
+        let fare_components = self.fare_component_analyzer.finalize()?;
+        let anomalies = self.anomaly_analyzer.finalize()?;
+        let tariff_discrepancies = self.tariff_analyzer.finalize()?;
+        let tips = self.tip_analyzer.finalize()?;
+        let store_forward = self.store_forward_analyzer.finalize()?;
+        let concurrency = self.concurrency_analyzer.finalize()?;
+        let net_flows = self.net_flow_analyzer.finalize()?;
 
         Ok(MultiAnalysisResults {
             peak_zones,
             hourly_patterns,
             payment_analysis,
+            od_matrix,
+            grid_cells,
+            detour,
+            airports,
+            poi_activity,
+            weekday_hour,
+            vendors,
+            rate_codes,
+            occupancy,
+            speeds,
+            top_routes,
+            time_series,
+            fare_components,
+            anomalies,
+            tariff_discrepancies,
+            tips,
+            store_forward,
+            concurrency,
+            net_flows,
         })
     }
 }
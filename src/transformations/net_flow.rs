@@ -0,0 +1,33 @@
+//! Defines the structs produced by the net flow analysis.
+use crate::models::datetime_format;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZoneNetFlow {
+    /// Zone of the pickups and dropoffs
+    pub zone_id: String,
+
+    /// Flow of each hour with pickups or dropoffs in the zone, sorted by hour
+    pub series: Vec<HourlyNetFlow>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HourlyNetFlow {
+    /// Start of the hour
+    #[serde(with = "datetime_format")]
+    pub hour: DateTime<Utc>,
+
+    /// Number of trips that started in the zone during the hour
+    pub pickups: usize,
+
+    /// Number of trips that ended in the zone during the hour
+    pub dropoffs: usize,
+
+    /// Pickups minus dropoffs; negative when cabs accumulate in the zone and
+    /// positive when they drain from it
+    pub net_flow: i64,
+
+    /// Net flow accumulated since the first hour of the series
+    pub cumulative_net_flow: i64,
+}
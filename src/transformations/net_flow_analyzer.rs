@@ -0,0 +1,119 @@
+//! Net flow analysis module
+//!
+//! Counts the pickups and dropoffs of each zone per hour and their difference, which
+//! shows where cabs accumulate and where they drain over time. Pickups are assigned to
+//! the hour and zone where the trip started and dropoffs to the ones where it ended.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use chrono::{DateTime, Timelike, Utc};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+use super::batch_aggregator::BatchAggregator;
+use super::net_flow::{HourlyNetFlow, ZoneNetFlow};
+
+type FlowKey = (String, DateTime<Utc>);
+type FlowData = (usize, usize);
+type FlowMap = HashMap<FlowKey, FlowData>;
+
+/// Batch aggregator for net flow analysis
+#[derive(Debug, Default)]
+pub struct NetFlowAnalyzer {
+    flow_stats: FlowMap,
+}
+
+/// Returns the start of the hour of the given time
+fn hour_start(datetime: &DateTime<Utc>) -> DateTime<Utc> {
+    datetime
+        .date_naive()
+        .and_hms_opt(datetime.hour(), 0, 0)
+        .expect("valid time")
+        .and_utc()
+}
+
+/// Adds the counts of `source` into `target`
+fn merge_flows(target: &mut FlowMap, source: FlowMap) {
+    for (key, (pickups, dropoffs)) in source {
+        let entry = target.entry(key).or_default();
+        entry.0 += pickups;
+        entry.1 += dropoffs;
+    }
+}
+
+impl BatchAggregator<Vec<ZoneNetFlow>> for NetFlowAnalyzer {
+    type Accumulator = FlowMap;
+
+    /// Process a single batch and accumulate intermediate results
+    /// Filters out trips with invalid pickup or dropoff coordinates
+    /// Accumulates pickups per pickup zone and hour and dropoffs per dropoff zone and hour
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::{get_zone_id, is_valid_nyc_coordinate};
+
+        let batch_acc = batch
+            .par_iter()
+            .filter(|trip| {
+                is_valid_nyc_coordinate(trip.pickup_latitude, trip.pickup_longitude)
+                    && is_valid_nyc_coordinate(trip.dropoff_latitude, trip.dropoff_longitude)
+            })
+            .fold(HashMap::new, |mut acc: FlowMap, trip| {
+                let pickup_zone = get_zone_id(trip.pickup_latitude, trip.pickup_longitude);
+                acc.entry((pickup_zone, hour_start(&trip.pickup_datetime)))
+                    .or_default()
+                    .0 += 1; // pickups
+                let dropoff_zone = get_zone_id(trip.dropoff_latitude, trip.dropoff_longitude);
+                acc.entry((dropoff_zone, hour_start(&trip.dropoff_datetime)))
+                    .or_default()
+                    .1 += 1; // dropoffs
+                acc
+            })
+            .reduce(HashMap::new, |mut acc1, acc2| {
+                merge_flows(&mut acc1, acc2);
+                acc1
+            });
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for accumulator in accumulators {
+            merge_flows(&mut self.flow_stats, accumulator);
+        }
+        Ok(())
+    }
+
+    /// Generates the time-ordered series of each zone, sorted by zone
+    fn finalize(self) -> Result<Vec<ZoneNetFlow>, ProcessingError> {
+        let mut zones: BTreeMap<String, BTreeMap<DateTime<Utc>, FlowData>> = BTreeMap::new();
+        for ((zone_id, hour), data) in self.flow_stats {
+            zones.entry(zone_id).or_default().insert(hour, data);
+        }
+
+        let results = zones
+            .into_iter()
+            .map(|(zone_id, hours)| {
+                let mut cumulative_net_flow = 0;
+                let series = hours
+                    .into_iter()
+                    .map(|(hour, (pickups, dropoffs))| {
+                        let net_flow = pickups as i64 - dropoffs as i64;
+                        cumulative_net_flow += net_flow;
+                        HourlyNetFlow {
+                            hour,
+                            pickups,
+                            dropoffs,
+                            net_flow,
+                            cumulative_net_flow,
+                        }
+                    })
+                    .collect();
+                ZoneNetFlow { zone_id, series }
+            })
+            .collect();
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,147 @@
+//! Passenger occupancy analysis module
+//!
+//! Calculates the distribution of passenger counts for each pickup zone and hour, along
+//! with passenger-miles and revenue per passenger. Rows where the passenger count is
+//! missing or zero are counted but left out of the per-passenger metrics.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use chrono::Timelike;
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+use super::batch_aggregator::BatchAggregator;
+use super::occupancy_stats::{OccupancyAnalysis, OccupancyStats, ZoneHourOccupancy};
+
+type OccupancyData = (usize, BTreeMap<i32, usize>, usize, f64, f64);
+type OccupancyMap = HashMap<(String, u32), OccupancyData>;
+
+/// Batch aggregator for passenger occupancy analysis
+#[derive(Debug, Default)]
+pub struct OccupancyAnalyzer {
+    zone_hour_stats: OccupancyMap,
+}
+
+/// Adds the totals of `source` into `target`
+fn merge_data(target: &mut OccupancyData, source: OccupancyData) {
+    let (count, distribution, passengers, passenger_miles, revenue) = source;
+    target.0 += count;
+    for (passenger_count, trips) in distribution {
+        *target.1.entry(passenger_count).or_insert(0) += trips;
+    }
+    target.2 += passengers;
+    target.3 += passenger_miles;
+    target.4 += revenue;
+}
+
+/// Builds the occupancy stats out of the accumulated totals
+fn occupancy_stats(data: OccupancyData) -> OccupancyStats {
+    use crate::utils::round_to_2_decimals;
+
+    let (count, distribution, passengers, passenger_miles, revenue) = data;
+    let with_passengers: usize = distribution
+        .iter()
+        .filter(|(&passenger_count, _)| passenger_count > 0)
+        .map(|(_, trips)| trips)
+        .sum();
+    let missing_or_zero = count - with_passengers;
+
+    OccupancyStats {
+        trip_count: count,
+        distribution,
+        missing_or_zero_percentage: round_to_2_decimals(
+            missing_or_zero as f64 * 100.0 / count as f64,
+        ),
+        avg_passengers: if with_passengers > 0 {
+            round_to_2_decimals(passengers as f64 / with_passengers as f64)
+        } else {
+            0.0
+        },
+        passenger_miles: round_to_2_decimals(passenger_miles),
+        revenue_per_passenger: if passengers > 0 {
+            round_to_2_decimals(revenue / passengers as f64)
+        } else {
+            0.0
+        },
+    }
+}
+
+impl BatchAggregator<OccupancyAnalysis> for OccupancyAnalyzer {
+    type Accumulator = OccupancyMap;
+
+    /// Process a single batch and accumulate intermediate results
+    /// Accumulates trip count, passenger count distribution (missing counts are recorded
+    /// as zero), total passengers, passenger-miles and revenue of the trips with
+    /// passengers per pickup zone and hour
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::get_zone_id;
+
+        let batch_acc = batch
+            .par_iter()
+            .fold(HashMap::new, |mut acc: OccupancyMap, trip| {
+                let key = (
+                    get_zone_id(trip.pickup_latitude, trip.pickup_longitude),
+                    trip.pickup_datetime.hour(),
+                );
+                let passenger_count = trip.passenger_count.unwrap_or(0).max(0);
+                let entry = acc.entry(key).or_insert((0, BTreeMap::new(), 0, 0.0, 0.0));
+                entry.0 += 1; // trip count
+                *entry.1.entry(passenger_count).or_insert(0) += 1; // distribution
+                if passenger_count > 0 {
+                    entry.2 += passenger_count as usize; // total passengers
+                    entry.3 += passenger_count as f64 * trip.trip_distance; // passenger-miles
+                    entry.4 += trip.total_amount; // revenue
+                }
+                acc
+            })
+            .reduce(HashMap::new, |mut acc1, acc2| {
+                for (key, data) in acc2 {
+                    let entry = acc1.entry(key).or_insert((0, BTreeMap::new(), 0, 0.0, 0.0));
+                    merge_data(entry, data);
+                }
+                acc1
+            });
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for accumulator in accumulators {
+            for (key, data) in accumulator {
+                let entry =
+                    self.zone_hour_stats
+                        .entry(key)
+                        .or_insert((0, BTreeMap::new(), 0, 0.0, 0.0));
+                merge_data(entry, data);
+            }
+        }
+        Ok(())
+    }
+
+    /// Generates the overall occupancy and the occupancy by zone and hour
+    fn finalize(self) -> Result<OccupancyAnalysis, ProcessingError> {
+        let mut totals: OccupancyData = (0, BTreeMap::new(), 0, 0.0, 0.0);
+        for data in self.zone_hour_stats.values() {
+            merge_data(&mut totals, data.clone());
+        }
+
+        let mut by_zone_hour: Vec<ZoneHourOccupancy> = self
+            .zone_hour_stats
+            .into_iter()
+            .map(|((zone_id, hour), data)| ZoneHourOccupancy {
+                zone_id,
+                hour,
+                stats: occupancy_stats(data),
+            })
+            .collect();
+        by_zone_hour.sort_by(|a, b| a.zone_id.cmp(&b.zone_id).then(a.hour.cmp(&b.hour)));
+
+        Ok(OccupancyAnalysis {
+            summary: occupancy_stats(totals),
+            by_zone_hour,
+        })
+    }
+}
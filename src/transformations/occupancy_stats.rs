@@ -0,0 +1,45 @@
+//! Defines the structs produced by the passenger occupancy analysis.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OccupancyAnalysis {
+    /// Occupancy over the whole dataset
+    pub summary: OccupancyStats,
+
+    /// Occupancy by pickup zone and hour, sorted by zone and hour
+    pub by_zone_hour: Vec<ZoneHourOccupancy>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZoneHourOccupancy {
+    /// Pickup zone
+    pub zone_id: String,
+
+    /// Hour of the day (0-23)
+    pub hour: u32,
+
+    #[serde(flatten)]
+    pub stats: OccupancyStats,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OccupancyStats {
+    /// Number of trips, including the ones without a passenger count
+    pub trip_count: usize,
+
+    /// Number of trips for each passenger count
+    pub distribution: BTreeMap<i32, usize>,
+
+    /// Percentage of trips where the passenger count is missing or zero
+    pub missing_or_zero_percentage: f64,
+
+    /// Average passengers per trip, over the trips with a passenger count
+    pub avg_passengers: f64,
+
+    /// Sum of the distance traveled by each passenger, in miles
+    pub passenger_miles: f64,
+
+    /// Total revenue divided by the number of passengers carried
+    pub revenue_per_passenger: f64,
+}
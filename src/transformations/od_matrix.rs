@@ -0,0 +1,26 @@
+//! Defines the OdCell struct used in origin-destination matrix analysis.
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OdCell {
+    /// Hour of the day (0-23) when the trip started
+    pub hour: u32,
+
+    /// Zone where the meter was engaged
+    pub pickup_zone: String,
+
+    /// Zone where the meter was disengaged
+    pub dropoff_zone: String,
+
+    /// Number of trips between both zones during the specified hour
+    pub trip_count: usize,
+
+    /// Total revenue generated
+    pub total_revenue: f64,
+
+    /// Average trip distance in miles
+    pub avg_distance: f64,
+
+    /// Median travel time in minutes
+    pub median_duration: f64,
+}
@@ -0,0 +1,136 @@
+//! Origin-destination matrix analysis module
+//!
+//! Builds a zone-to-zone matrix per hour of the day using both pickup and dropoff
+//! coordinates. For each cell calculates the amount of trips, the total revenue,
+//! the average distance and the median travel time.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use chrono::Timelike;
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+use super::batch_aggregator::BatchAggregator;
+use super::od_matrix::OdCell;
+
+type OdKey = (String, String, u32);
+type DurationHistogram = BTreeMap<i64, usize>;
+type OdStatsData = (usize, f64, f64, DurationHistogram);
+type OdStatsMap = HashMap<OdKey, OdStatsData>;
+
+/// Batch aggregator for origin-destination matrix analysis
+#[derive(Debug, Default)]
+pub struct OdMatrixAnalyzer {
+    od_stats: OdStatsMap,
+}
+
+/// Adds the values of `other` into `entry`
+fn merge_od_stats(entry: &mut OdStatsData, other: OdStatsData) {
+    let (count, revenue, distance, durations) = other;
+    entry.0 += count;
+    entry.1 += revenue;
+    entry.2 += distance;
+    for (minutes, minutes_count) in durations {
+        *entry.3.entry(minutes).or_insert(0) += minutes_count;
+    }
+}
+
+impl BatchAggregator<Vec<OdCell>> for OdMatrixAnalyzer {
+    type Accumulator = OdStatsMap;
+
+    /// Process a single batch and accumulate intermediate results
+    /// Filters out trips with missing pickup or dropoff coordinates, non-positive
+    /// total amounts or dropoffs before pickups
+    /// Accumulates trip count, total revenue, total distance and a histogram of
+    /// durations (in seconds) per pickup zone, dropoff zone and hour
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::get_zone_id;
+
+        let batch_stats: OdStatsMap = batch
+            .par_iter()
+            .filter(|trip| {
+                trip.pickup_latitude != 0.0
+                    && trip.pickup_longitude != 0.0
+                    && trip.dropoff_latitude != 0.0
+                    && trip.dropoff_longitude != 0.0
+                    && trip.total_amount > 0.0
+                    && trip.dropoff_datetime >= trip.pickup_datetime
+            })
+            .fold(HashMap::new, |mut acc, trip| {
+                let hour = trip.pickup_datetime.hour();
+                let pickup_zone = get_zone_id(trip.pickup_latitude, trip.pickup_longitude);
+                let dropoff_zone = get_zone_id(trip.dropoff_latitude, trip.dropoff_longitude);
+                let duration = (trip.dropoff_datetime - trip.pickup_datetime).num_seconds();
+                let entry = acc
+                    .entry((pickup_zone, dropoff_zone, hour))
+                    .or_insert_with(|| (0, 0.0, 0.0, BTreeMap::new()));
+                entry.0 += 1; // trip count
+                entry.1 += trip.total_amount; // total revenue
+                entry.2 += trip.trip_distance; // total distance
+                *entry.3.entry(duration).or_insert(0) += 1; // duration histogram
+                acc
+            })
+            .reduce(HashMap::new, |mut acc1, acc2| {
+                for (key, stats) in acc2 {
+                    let entry = acc1
+                        .entry(key)
+                        .or_insert_with(|| (0, 0.0, 0.0, BTreeMap::new()));
+                    merge_od_stats(entry, stats);
+                }
+                acc1
+            });
+
+        Ok(batch_stats)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for accumulator in accumulators {
+            for (key, stats) in accumulator {
+                let entry = self
+                    .od_stats
+                    .entry(key)
+                    .or_insert_with(|| (0, 0.0, 0.0, BTreeMap::new()));
+                merge_od_stats(entry, stats);
+            }
+        }
+        Ok(())
+    }
+
+    /// Generates the matrix cells sorted by hour, pickup zone and dropoff zone
+    fn finalize(self) -> Result<Vec<OdCell>, ProcessingError> {
+        use crate::utils::{quantile_from_histogram, round_to_2_decimals};
+
+        let mut cells: Vec<OdCell> = self
+            .od_stats
+            .into_iter()
+            .map(
+                |((pickup_zone, dropoff_zone, hour), (count, revenue, distance, durations))| {
+                    OdCell {
+                        hour,
+                        pickup_zone,
+                        dropoff_zone,
+                        trip_count: count,
+                        total_revenue: round_to_2_decimals(revenue),
+                        avg_distance: round_to_2_decimals(distance / count as f64),
+                        median_duration: round_to_2_decimals(
+                            quantile_from_histogram(&durations, 0.5).unwrap_or(0) as f64 / 60.0,
+                        ),
+                    }
+                },
+            )
+            .collect();
+
+        cells.sort_by(|a, b| {
+            (a.hour, &a.pickup_zone, &a.dropoff_zone).cmp(&(
+                b.hour,
+                &b.pickup_zone,
+                &b.dropoff_zone,
+            ))
+        });
+
+        Ok(cells)
+    }
+}
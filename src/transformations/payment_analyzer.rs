@@ -1,16 +1,18 @@
 //! Payment analysis transformation module
 //!
-//! Analyzes the amount of trips, the total amount charged, the average amount per trip,
-//! and the percentage of trips for each payment type (like credit card, cash, etc.)
+//! Analyzes the amount of trips, the total amount charged, the average, percentiles,
+//! standard deviation, minimum and maximum of the amount per trip, and the percentage
+//! of trips for each payment type (like credit card, cash, etc.)
 use crate::error::ProcessingError;
 use crate::models::TaxiTrip;
+use crate::sketches::{Moments, QuantileSketch};
 use rayon::prelude::*;
 use std::collections::HashMap;
 
 use super::batch_aggregator::BatchAggregator;
 use super::payment_stats::PaymentStats;
 
-type PaymentStatsData = (usize, f64);
+type PaymentStatsData = (usize, f64, QuantileSketch, Moments);
 type PaymentStatsMap = HashMap<i32, PaymentStatsData>;
 
 /// Batch aggregator for payment analysis
@@ -25,7 +27,8 @@ impl BatchAggregator<Vec<PaymentStats>> for PaymentAnalyzer {
 
     /// Process a single batch and accumulate intermediate results
     /// Filters out trips with invalid payment types or non-positive total amounts
-    /// Accumulates trip count and total amount per payment type
+    /// Accumulates trip count, total amount, and a sketch and the moments of the amounts
+    /// per payment type
     fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
         let valid_trips: Vec<&TaxiTrip> = batch
             .iter()
@@ -36,17 +39,21 @@ impl BatchAggregator<Vec<PaymentStats>> for PaymentAnalyzer {
 
         let batch_stats: PaymentStatsMap = valid_trips
             .par_iter()
-            .fold(HashMap::new, |mut acc, trip| {
-                let entry = acc.entry(trip.payment_type).or_insert((0, 0.0));
+            .fold(HashMap::new, |mut acc: PaymentStatsMap, trip| {
+                let entry = acc.entry(trip.payment_type).or_default();
                 entry.0 += 1;
                 entry.1 += trip.total_amount;
+                entry.2.add(trip.total_amount);
+                entry.3.add(trip.total_amount);
                 acc
             })
             .reduce(HashMap::new, |mut acc1, acc2| {
-                for (payment_type, (count, amount)) in acc2 {
-                    let entry = acc1.entry(payment_type).or_insert((0, 0.0));
+                for (payment_type, (count, amount, amounts, moments)) in acc2 {
+                    let entry = acc1.entry(payment_type).or_default();
                     entry.0 += count;
                     entry.1 += amount;
+                    entry.2.merge(&amounts);
+                    entry.3.merge(&moments);
                 }
                 acc1
             });
@@ -61,10 +68,12 @@ impl BatchAggregator<Vec<PaymentStats>> for PaymentAnalyzer {
     ) -> Result<(), ProcessingError> {
         for (accumulator, valid_count) in accumulators {
             self.total_valid_trips += valid_count;
-            for (payment_type, (count, amount)) in accumulator {
-                let entry = self.payment_stats.entry(payment_type).or_insert((0, 0.0));
+            for (payment_type, (count, amount, amounts, moments)) in accumulator {
+                let entry = self.payment_stats.entry(payment_type).or_default();
                 entry.0 += count;
                 entry.1 += amount;
+                entry.2.merge(&amounts);
+                entry.3.merge(&moments);
             }
         }
         Ok(())
@@ -77,15 +86,19 @@ impl BatchAggregator<Vec<PaymentStats>> for PaymentAnalyzer {
         let payment_results: Vec<PaymentStats> = self
             .payment_stats
             .into_iter()
-            .map(|(payment_type, (count, total_amount))| PaymentStats {
-                payment_type,
-                trip_count: count,
-                total_amount: round_to_2_decimals(total_amount),
-                avg_amount: round_to_2_decimals(total_amount / count as f64),
-                percentage: round_to_2_decimals(
-                    (count as f64 / self.total_valid_trips as f64) * 100.0,
-                ),
-            })
+            .map(
+                |(payment_type, (count, total_amount, amounts, moments))| PaymentStats {
+                    payment_type,
+                    trip_count: count,
+                    total_amount: round_to_2_decimals(total_amount),
+                    avg_amount: round_to_2_decimals(total_amount / count as f64),
+                    amount_percentiles: amounts.percentiles(),
+                    amount_spread: moments.spread(),
+                    percentage: round_to_2_decimals(
+                        (count as f64 / self.total_valid_trips as f64) * 100.0,
+                    ),
+                },
+            )
             .collect();
 
         Ok(payment_results)
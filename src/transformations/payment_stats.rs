@@ -1,4 +1,5 @@
 //! A struct to hold payment statistics
+use crate::sketches::{Percentiles, Spread};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -17,6 +18,12 @@ pub struct PaymentStats {
     /// Average amount charged per trip
     pub avg_amount: f64,
 
+    /// Median, 90th and 99th percentiles of the amount charged per trip
+    pub amount_percentiles: Percentiles,
+
+    /// Standard deviation, minimum and maximum of the amount charged per trip
+    pub amount_spread: Spread,
+
     /// Percentage of trips for this payment type relative to all valid trips
     pub percentage: f64,
 }
@@ -1,6 +1,11 @@
 //! Defines the PeakZone struct used in peak zone analysis.
 use serde::{Deserialize, Serialize};
 
+use crate::error::ProcessingError;
+use crate::models::{Feature, Geometry};
+use crate::sketches::{Percentiles, Spread};
+use crate::utils::get_zone_bounds;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PeakZone {
     /// Name of the zone
@@ -15,12 +20,40 @@ pub struct PeakZone {
     /// Total revenue generated
     pub total_revenue: f64,
 
+    /// Standard deviation, minimum and maximum of the revenue per trip
+    pub revenue_spread: Spread,
+
     /// Average fare amount
     pub avg_fare: f64,
 
+    /// Median, 90th and 99th percentiles of the fare amount
+    pub fare_percentiles: Percentiles,
+
+    /// Standard deviation, minimum and maximum of the fare amount
+    pub fare_spread: Spread,
+
+    /// Approximate number of distinct geohash cells with pickups
+    pub distinct_pickup_cells: u64,
+
+    /// Approximate number of distinct days with pickups
+    pub active_days: u64,
+
     /// Center latitude of the zone based on trip coordinates
     pub center_lat: f64,
 
     /// Center longitude of the zone based on trip coordinates
     pub center_lng: f64,
 }
+
+impl PeakZone {
+    /// Builds a GeoJSON feature with all the stats as properties.
+    /// The geometry is the zone rectangle when `with_polygon` is set and the zone has
+    /// known bounds, otherwise the center point of its trips
+    pub fn to_feature(&self, with_polygon: bool) -> Result<Feature, ProcessingError> {
+        let geometry = match get_zone_bounds(&self.zone_name) {
+            Some(bounds) if with_polygon => Geometry::rectangle(&bounds),
+            _ => Geometry::point(self.center_lat, self.center_lng),
+        };
+        Ok(Feature::new(geometry, self)?)
+    }
+}
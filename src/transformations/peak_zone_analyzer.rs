@@ -1,11 +1,14 @@
 //! Peak zones analysis module
 //!
 //! Analyzes taxi trip data to identify peak zones based on pickup locations and times.
-//! Calculates total revenue, average fare, amount of trips for each zone and hour and
-//! determines the center coordinates of each zone. Identifies the top 50 zones in
-//! specific hours with the highest revenue.
+//! Calculates total revenue, average fare, fare percentiles, standard deviation, minimum
+//! and maximum of revenue and fare, amount of trips for each zone and hour and
+//! determines the center coordinates of each zone. The number of distinct geohash cells
+//! and days with pickups are estimated with HyperLogLog sketches. Identifies the top 50
+//! zones in specific hours with the highest revenue.
 use crate::error::ProcessingError;
 use crate::models::TaxiTrip;
+use crate::sketches::{HyperLogLog, Moments, QuantileSketch};
 use chrono::Timelike;
 use rayon::prelude::*;
 use std::collections::HashMap;
@@ -14,25 +17,85 @@ use super::batch_aggregator::BatchAggregator;
 use super::peak_zone::PeakZone;
 
 type ZoneKey = (String, u32);
-type ZoneStatsData = (usize, f64, f64, f64, f64, usize);
+type ZoneStatsData = (
+    usize,
+    f64,
+    f64,
+    f64,
+    f64,
+    usize,
+    QuantileSketch,
+    Moments,
+    Moments,
+    HyperLogLog,
+    HyperLogLog,
+);
 type ZoneStatsMap = HashMap<ZoneKey, ZoneStatsData>;
 
 /// Batch aggregator for peak zone analysis
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct PeakZoneAnalyzer {
+    cell_precision: usize,
     zone_stats: ZoneStatsMap,
 }
 
+impl PeakZoneAnalyzer {
+    /// Creates an analyzer that counts distinct pickup cells using geohash cells of
+    /// `cell_precision` characters
+    pub fn with_cell_precision(cell_precision: usize) -> Self {
+        Self {
+            cell_precision,
+            zone_stats: HashMap::new(),
+        }
+    }
+}
+
+impl Default for PeakZoneAnalyzer {
+    fn default() -> Self {
+        Self::with_cell_precision(crate::config::AnalysisConfig::default().grid_precision)
+    }
+}
+
+/// Adds the totals and sketches of `source` into `target`
+fn merge_data(target: &mut ZoneStatsData, source: ZoneStatsData) {
+    let (
+        count,
+        total,
+        fare,
+        lat_sum,
+        lng_sum,
+        coord_count,
+        fares,
+        revenues,
+        fare_moments,
+        cells,
+        days,
+    ) = source;
+    target.0 += count;
+    target.1 += total;
+    target.2 += fare;
+    target.3 += lat_sum;
+    target.4 += lng_sum;
+    target.5 += coord_count;
+    target.6.merge(&fares);
+    target.7.merge(&revenues);
+    target.8.merge(&fare_moments);
+    target.9.merge(&cells);
+    target.10.merge(&days);
+}
+
 impl BatchAggregator<Vec<PeakZone>> for PeakZoneAnalyzer {
     type Accumulator = ZoneStatsMap;
 
     /// Process a single batch and accumulate intermediate results
     /// Filters out trips with invalid coordinates or non-positive total amounts
-    /// Accumulates trip count, total revenue, total fare, and sums of coordinates per
-    /// zone and hour
+    /// Accumulates trip count, total revenue, total fare, sums of coordinates, a
+    /// sketch of the fares, the moments of revenues and fares, and distinct-count
+    /// sketches of pickup cells and days per zone and hour
     fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
-        use crate::utils::get_zone_id;
+        use crate::utils::{encode_geohash, get_zone_id};
 
+        let cell_precision = self.cell_precision;
         let batch_stats: ZoneStatsMap = batch
             .par_iter()
             .filter(|trip| {
@@ -40,30 +103,29 @@ impl BatchAggregator<Vec<PeakZone>> for PeakZoneAnalyzer {
                     && trip.pickup_longitude != 0.0
                     && trip.total_amount > 0.0
             })
-            .fold(HashMap::new, |mut acc, trip| {
+            .fold(HashMap::new, |mut acc: ZoneStatsMap, trip| {
                 let hour = trip.pickup_datetime.hour();
                 let zone_id = get_zone_id(trip.pickup_latitude, trip.pickup_longitude);
+                let cell =
+                    encode_geohash(trip.pickup_latitude, trip.pickup_longitude, cell_precision);
                 let key = (zone_id, hour);
-                let entry = acc.entry(key).or_insert((0, 0.0, 0.0, 0.0, 0.0, 0));
+                let entry = acc.entry(key).or_default();
                 entry.0 += 1; // trip count
                 entry.1 += trip.total_amount; // total revenue
                 entry.2 += trip.fare_amount; // total fare
                 entry.3 += trip.pickup_latitude; // lat sum for averaging
                 entry.4 += trip.pickup_longitude; // lng sum for averaging
                 entry.5 += 1; // coordinate count for averaging
+                entry.6.add(trip.fare_amount); // fare sketch
+                entry.7.add(trip.total_amount); // revenue moments
+                entry.8.add(trip.fare_amount); // fare moments
+                entry.9.add(&cell); // distinct pickup cells
+                entry.10.add(&trip.pickup_datetime.date_naive()); // distinct days
                 acc
             })
             .reduce(HashMap::new, |mut acc1, acc2| {
-                for ((zone_id, hour), (count, total, fare, lat_sum, lng_sum, coord_count)) in acc2 {
-                    let entry = acc1
-                        .entry((zone_id, hour))
-                        .or_insert((0, 0.0, 0.0, 0.0, 0.0, 0));
-                    entry.0 += count;
-                    entry.1 += total;
-                    entry.2 += fare;
-                    entry.3 += lat_sum;
-                    entry.4 += lng_sum;
-                    entry.5 += coord_count;
+                for (key, data) in acc2 {
+                    merge_data(acc1.entry(key).or_default(), data);
                 }
                 acc1
             });
@@ -77,19 +139,8 @@ impl BatchAggregator<Vec<PeakZone>> for PeakZoneAnalyzer {
         accumulators: Vec<Self::Accumulator>,
     ) -> Result<(), ProcessingError> {
         for accumulator in accumulators {
-            for ((zone_id, hour), (count, total, fare, lat_sum, lng_sum, coord_count)) in
-                accumulator
-            {
-                let entry = self
-                    .zone_stats
-                    .entry((zone_id, hour))
-                    .or_insert((0, 0.0, 0.0, 0.0, 0.0, 0));
-                entry.0 += count;
-                entry.1 += total;
-                entry.2 += fare;
-                entry.3 += lat_sum;
-                entry.4 += lng_sum;
-                entry.5 += coord_count;
+            for (key, data) in accumulator {
+                merge_data(self.zone_stats.entry(key).or_default(), data);
             }
         }
         Ok(())
@@ -105,13 +156,30 @@ impl BatchAggregator<Vec<PeakZone>> for PeakZoneAnalyzer {
             .map(
                 |(
                     (zone_id, hour),
-                    (count, total_revenue, total_fare, lat_sum, lng_sum, coord_count),
+                    (
+                        count,
+                        total_revenue,
+                        total_fare,
+                        lat_sum,
+                        lng_sum,
+                        coord_count,
+                        fares,
+                        revenues,
+                        fare_moments,
+                        cells,
+                        days,
+                    ),
                 )| PeakZone {
                     zone_name: zone_id,
                     hour,
                     trip_count: count,
                     total_revenue: round_to_2_decimals(total_revenue),
+                    revenue_spread: revenues.spread(),
                     avg_fare: round_to_2_decimals(total_fare / count as f64),
+                    fare_percentiles: fares.percentiles(),
+                    fare_spread: fare_moments.spread(),
+                    distinct_pickup_cells: cells.estimate(),
+                    active_days: days.estimate(),
                     center_lat: round_to_2_decimals(lat_sum / coord_count as f64),
                     center_lng: round_to_2_decimals(lng_sum / coord_count as f64),
                 },
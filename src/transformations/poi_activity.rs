@@ -0,0 +1,20 @@
+//! Defines the PoiActivity struct used in points of interest analysis.
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PoiActivity {
+    /// Name of the point of interest
+    pub poi_name: String,
+
+    /// Hour of the day (0-23)
+    pub hour: u32,
+
+    /// Number of trips that started near the point during the specified hour
+    pub pickup_count: usize,
+
+    /// Number of trips that ended near the point during the specified hour
+    pub dropoff_count: usize,
+
+    /// Total revenue of the trips that started near the point
+    pub total_revenue: f64,
+}
@@ -0,0 +1,100 @@
+//! Points of interest analysis module
+//!
+//! Uses the nearest POI tags added to each trip by the POI enrichment to count
+//! pickups and dropoffs near each point of interest per hour (e.g. pickups within
+//! 100 m of Penn Station), along with the revenue of the trips started there.
+//! Produces no results when the trips were not enriched.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use chrono::Timelike;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use super::batch_aggregator::BatchAggregator;
+use super::poi_activity::PoiActivity;
+
+type PoiKey = (String, u32);
+type PoiStatsData = (usize, usize, f64);
+type PoiStatsMap = HashMap<PoiKey, PoiStatsData>;
+
+/// Batch aggregator for points of interest analysis
+#[derive(Debug, Default)]
+pub struct PoiAnalyzer {
+    poi_stats: PoiStatsMap,
+}
+
+impl BatchAggregator<Vec<PoiActivity>> for PoiAnalyzer {
+    type Accumulator = PoiStatsMap;
+
+    /// Process a single batch and accumulate intermediate results
+    /// Only trips tagged with a POI at the pickup or the dropoff are considered
+    /// Accumulates pickup count, dropoff count and pickup revenue per POI and hour
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        let batch_stats: PoiStatsMap = batch
+            .par_iter()
+            .filter(|trip| trip.pickup_poi.is_some() || trip.dropoff_poi.is_some())
+            .fold(HashMap::new, |mut acc, trip| {
+                let hour = trip.pickup_datetime.hour();
+                if let Some(poi) = &trip.pickup_poi {
+                    let entry = acc.entry((poi.clone(), hour)).or_insert((0, 0, 0.0));
+                    entry.0 += 1; // pickup count
+                    entry.2 += trip.total_amount; // pickup revenue
+                }
+                if let Some(poi) = &trip.dropoff_poi {
+                    let entry = acc.entry((poi.clone(), hour)).or_insert((0, 0, 0.0));
+                    entry.1 += 1; // dropoff count
+                }
+                acc
+            })
+            .reduce(HashMap::new, |mut acc1, acc2| {
+                for (key, (pickups, dropoffs, revenue)) in acc2 {
+                    let entry = acc1.entry(key).or_insert((0, 0, 0.0));
+                    entry.0 += pickups;
+                    entry.1 += dropoffs;
+                    entry.2 += revenue;
+                }
+                acc1
+            });
+
+        Ok(batch_stats)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for accumulator in accumulators {
+            for (key, (pickups, dropoffs, revenue)) in accumulator {
+                let entry = self.poi_stats.entry(key).or_insert((0, 0, 0.0));
+                entry.0 += pickups;
+                entry.1 += dropoffs;
+                entry.2 += revenue;
+            }
+        }
+        Ok(())
+    }
+
+    /// Generates the activity sorted by POI name and hour
+    fn finalize(self) -> Result<Vec<PoiActivity>, ProcessingError> {
+        use crate::utils::round_to_2_decimals;
+
+        let mut activity: Vec<PoiActivity> = self
+            .poi_stats
+            .into_iter()
+            .map(
+                |((poi_name, hour), (pickups, dropoffs, revenue))| PoiActivity {
+                    poi_name,
+                    hour,
+                    pickup_count: pickups,
+                    dropoff_count: dropoffs,
+                    total_revenue: round_to_2_decimals(revenue),
+                },
+            )
+            .collect();
+
+        activity.sort_by(|a, b| (&a.poi_name, a.hour).cmp(&(&b.poi_name, b.hour)));
+
+        Ok(activity)
+    }
+}
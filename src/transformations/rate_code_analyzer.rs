@@ -0,0 +1,175 @@
+//! Rate code analysis module
+//!
+//! Calculates trips, revenue, average distance and average duration for each rate code
+//! (standard, JFK, Newark, Nassau/Westchester, negotiated and group ride), along with
+//! how often negotiated fares are used in each pickup zone and hour.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use chrono::Timelike;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use super::batch_aggregator::BatchAggregator;
+use super::rate_code_stats::{NegotiatedFareUsage, RateCodeAnalysis, RateCodeStats};
+
+/// Rate code of negotiated fares
+pub const NEGOTIATED_RATE_CODE: i32 = 5;
+
+type RateCodeData = (usize, f64, f64, f64);
+type RateCodeMap = HashMap<i32, RateCodeData>;
+type ZoneHourData = (usize, usize, f64);
+type ZoneHourMap = HashMap<(String, u32), ZoneHourData>;
+
+/// Batch aggregator for rate code analysis
+#[derive(Debug, Default)]
+pub struct RateCodeAnalyzer {
+    rate_code_stats: RateCodeMap,
+    zone_hour_stats: ZoneHourMap,
+}
+
+/// Returns the name of the rate code
+fn rate_code_name(rate_code_id: i32) -> &'static str {
+    match rate_code_id {
+        1 => "Standard rate",
+        2 => "JFK",
+        3 => "Newark",
+        4 => "Nassau or Westchester",
+        5 => "Negotiated fare",
+        6 => "Group ride",
+        _ => "Unknown",
+    }
+}
+
+impl BatchAggregator<RateCodeAnalysis> for RateCodeAnalyzer {
+    type Accumulator = (RateCodeMap, ZoneHourMap);
+
+    /// Process a single batch and accumulate intermediate results
+    /// Filters out trips with non-positive total amounts or durations
+    /// Accumulates trip count, total revenue, total distance and total duration per
+    /// rate code, and trip count, negotiated trip count and negotiated fare per
+    /// pickup zone and hour
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::get_zone_id;
+
+        let batch_acc = batch
+            .par_iter()
+            .filter(|trip| trip.total_amount > 0.0 && trip.dropoff_datetime > trip.pickup_datetime)
+            .fold(
+                || (HashMap::new(), HashMap::new()),
+                |(mut codes, mut zones): Self::Accumulator, trip| {
+                    let duration =
+                        (trip.dropoff_datetime - trip.pickup_datetime).num_seconds() as f64 / 60.0;
+                    let entry = codes.entry(trip.rate_code_id).or_insert((0, 0.0, 0.0, 0.0));
+                    entry.0 += 1; // trip count
+                    entry.1 += trip.total_amount; // total revenue
+                    entry.2 += trip.trip_distance; // total distance
+                    entry.3 += duration; // total duration
+
+                    let key = (
+                        get_zone_id(trip.pickup_latitude, trip.pickup_longitude),
+                        trip.pickup_datetime.hour(),
+                    );
+                    let entry = zones.entry(key).or_insert((0, 0, 0.0));
+                    entry.0 += 1; // trip count
+                    if trip.rate_code_id == NEGOTIATED_RATE_CODE {
+                        entry.1 += 1; // negotiated trip count
+                        entry.2 += trip.fare_amount; // negotiated fare
+                    }
+                    (codes, zones)
+                },
+            )
+            .reduce(
+                || (HashMap::new(), HashMap::new()),
+                |(mut codes1, mut zones1), (codes2, zones2)| {
+                    for (code, (count, revenue, distance, duration)) in codes2 {
+                        let entry = codes1.entry(code).or_insert((0, 0.0, 0.0, 0.0));
+                        entry.0 += count;
+                        entry.1 += revenue;
+                        entry.2 += distance;
+                        entry.3 += duration;
+                    }
+                    for (key, (count, negotiated, fare)) in zones2 {
+                        let entry = zones1.entry(key).or_insert((0, 0, 0.0));
+                        entry.0 += count;
+                        entry.1 += negotiated;
+                        entry.2 += fare;
+                    }
+                    (codes1, zones1)
+                },
+            );
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for (codes, zones) in accumulators {
+            for (code, (count, revenue, distance, duration)) in codes {
+                let entry = self
+                    .rate_code_stats
+                    .entry(code)
+                    .or_insert((0, 0.0, 0.0, 0.0));
+                entry.0 += count;
+                entry.1 += revenue;
+                entry.2 += distance;
+                entry.3 += duration;
+            }
+            for (key, (count, negotiated, fare)) in zones {
+                let entry = self.zone_hour_stats.entry(key).or_insert((0, 0, 0.0));
+                entry.0 += count;
+                entry.1 += negotiated;
+                entry.2 += fare;
+            }
+        }
+        Ok(())
+    }
+
+    /// Generates the rate code stats sorted by rate code, and the negotiated fare usage
+    /// of the zones and hours with at least one negotiated trip, sorted by zone and hour
+    fn finalize(self) -> Result<RateCodeAnalysis, ProcessingError> {
+        use crate::utils::round_to_2_decimals;
+
+        let mut rate_codes: Vec<RateCodeStats> = self
+            .rate_code_stats
+            .into_iter()
+            .map(
+                |(rate_code_id, (count, revenue, distance, duration))| RateCodeStats {
+                    rate_code_id,
+                    rate_code_name: rate_code_name(rate_code_id).to_string(),
+                    trip_count: count,
+                    total_revenue: round_to_2_decimals(revenue),
+                    avg_distance: round_to_2_decimals(distance / count as f64),
+                    avg_duration: round_to_2_decimals(duration / count as f64),
+                },
+            )
+            .collect();
+        rate_codes.sort_by_key(|stats| stats.rate_code_id);
+
+        let mut negotiated_fares: Vec<NegotiatedFareUsage> = self
+            .zone_hour_stats
+            .into_iter()
+            .filter(|(_, (_, negotiated, _))| *negotiated > 0)
+            .map(
+                |((zone_id, hour), (count, negotiated, fare))| NegotiatedFareUsage {
+                    zone_id,
+                    hour,
+                    negotiated_trips: negotiated,
+                    total_trips: count,
+                    negotiated_percentage: round_to_2_decimals(
+                        negotiated as f64 * 100.0 / count as f64,
+                    ),
+                    avg_negotiated_fare: round_to_2_decimals(fare / negotiated as f64),
+                },
+            )
+            .collect();
+        negotiated_fares.sort_by(|a, b| a.zone_id.cmp(&b.zone_id).then(a.hour.cmp(&b.hour)));
+
+        Ok(RateCodeAnalysis {
+            rate_codes,
+            negotiated_fares,
+        })
+    }
+}
@@ -0,0 +1,53 @@
+//! Defines the structs produced by the rate code analysis.
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RateCodeAnalysis {
+    /// Stats for each rate code, sorted by rate code id
+    pub rate_codes: Vec<RateCodeStats>,
+
+    /// Negotiated fare usage by pickup zone and hour
+    pub negotiated_fares: Vec<NegotiatedFareUsage>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RateCodeStats {
+    /// Rate code as reported by the meter (1-6)
+    pub rate_code_id: i32,
+
+    /// Name of the rate code
+    pub rate_code_name: String,
+
+    /// Number of trips
+    pub trip_count: usize,
+
+    /// Total revenue
+    pub total_revenue: f64,
+
+    /// Average trip distance in miles
+    pub avg_distance: f64,
+
+    /// Average trip duration in minutes
+    pub avg_duration: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NegotiatedFareUsage {
+    /// Pickup zone
+    pub zone_id: String,
+
+    /// Hour of the day (0-23)
+    pub hour: u32,
+
+    /// Number of trips with a negotiated fare
+    pub negotiated_trips: usize,
+
+    /// Number of trips of any rate code
+    pub total_trips: usize,
+
+    /// Percentage of trips with a negotiated fare
+    pub negotiated_percentage: f64,
+
+    /// Average fare amount of the negotiated trips
+    pub avg_negotiated_fare: f64,
+}
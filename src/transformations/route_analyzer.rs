@@ -0,0 +1,122 @@
+//! Top routes analysis module
+//!
+//! Finds the most frequent pickup to dropoff geohash cell pairs. Counting every pair
+//! exactly at fine resolutions takes too much memory, so each batch is reduced to a
+//! Space-Saving summary of bounded size, and summaries are merged across batches and
+//! files. Every reported count comes with the maximum amount it may be overestimated.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use crate::sketches::SpaceSaving;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use super::batch_aggregator::BatchAggregator;
+use super::top_route::{RouteCount, TopRoutes};
+
+/// Counters kept per route reported, extra counters make the estimates tighter
+const COUNTERS_PER_ROUTE: usize = 10;
+
+type RouteKey = (String, String);
+
+/// Batch aggregator for top routes analysis
+#[derive(Debug)]
+pub struct RouteAnalyzer {
+    precision: usize,
+    top_n: usize,
+    routes: SpaceSaving<RouteKey>,
+    trips_counted: usize,
+}
+
+impl RouteAnalyzer {
+    /// Creates an analyzer that reports the `top_n` most frequent routes between
+    /// geohash cells of `precision` characters
+    pub fn new(precision: usize, top_n: usize) -> Self {
+        Self {
+            precision,
+            top_n,
+            routes: SpaceSaving::new(top_n * COUNTERS_PER_ROUTE),
+            trips_counted: 0,
+        }
+    }
+}
+
+impl Default for RouteAnalyzer {
+    fn default() -> Self {
+        let config = crate::config::AnalysisConfig::default();
+        Self::new(config.grid_precision, config.top_routes)
+    }
+}
+
+impl BatchAggregator<TopRoutes> for RouteAnalyzer {
+    type Accumulator = (SpaceSaving<RouteKey>, usize);
+
+    /// Process a single batch and accumulate intermediate results
+    /// Filters out trips whose pickup or dropoff is outside the NYC area
+    /// Counts the trips of each pickup and dropoff cell pair exactly within the batch,
+    /// and keeps a Space-Saving summary of the most frequent pairs
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::{encode_geohash, is_valid_nyc_coordinate};
+
+        let precision = self.precision;
+        let batch_counts: HashMap<RouteKey, u64> = batch
+            .par_iter()
+            .filter(|trip| {
+                is_valid_nyc_coordinate(trip.pickup_latitude, trip.pickup_longitude)
+                    && is_valid_nyc_coordinate(trip.dropoff_latitude, trip.dropoff_longitude)
+            })
+            .fold(HashMap::new, |mut acc, trip| {
+                let key = (
+                    encode_geohash(trip.pickup_latitude, trip.pickup_longitude, precision),
+                    encode_geohash(trip.dropoff_latitude, trip.dropoff_longitude, precision),
+                );
+                *acc.entry(key).or_insert(0) += 1;
+                acc
+            })
+            .reduce(HashMap::new, |mut acc1, acc2| {
+                for (key, count) in acc2 {
+                    *acc1.entry(key).or_insert(0) += count;
+                }
+                acc1
+            });
+
+        let trips_counted = batch_counts.values().sum::<u64>() as usize;
+        let summary = SpaceSaving::from_counts(self.top_n * COUNTERS_PER_ROUTE, batch_counts);
+
+        Ok((summary, trips_counted))
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for (summary, trips_counted) in accumulators {
+            self.routes.merge(&summary);
+            self.trips_counted += trips_counted;
+        }
+        Ok(())
+    }
+
+    /// Generates the most frequent routes with their error bounds
+    fn finalize(self) -> Result<TopRoutes, ProcessingError> {
+        let routes = self
+            .routes
+            .top(self.top_n)
+            .into_iter()
+            .map(|((pickup_cell, dropoff_cell), counter)| RouteCount {
+                pickup_cell,
+                dropoff_cell,
+                trip_count: counter.count,
+                max_overcount: counter.error,
+                min_trip_count: counter.count - counter.error,
+            })
+            .collect();
+
+        Ok(TopRoutes {
+            cell_precision: self.precision,
+            trips_counted: self.trips_counted,
+            error_bound: self.routes.error_bound(),
+            routes,
+        })
+    }
+}
@@ -0,0 +1,167 @@
+//! Speed and congestion analysis module
+//!
+//! Calculates average and percentile travel speeds per pickup zone and hour out of the
+//! meter distance and the trip duration. Speeds above a configurable threshold are
+//! treated as meter or timestamp errors and ignored. Each zone's overnight speed is
+//! used as its free-flow speed to compute a congestion index for every hour.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use chrono::Timelike;
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+use super::batch_aggregator::BatchAggregator;
+use super::speed_stats::SpeedStats;
+
+/// Hours of the day (from midnight to 5 AM) whose speed is taken as free-flow
+const FREE_FLOW_HOURS: std::ops::Range<u32> = 0..5;
+
+type SpeedKey = (String, u32);
+type SpeedData = (usize, f64, f64, BTreeMap<i64, usize>);
+type SpeedMap = HashMap<SpeedKey, SpeedData>;
+
+/// Batch aggregator for speed and congestion analysis
+#[derive(Debug)]
+pub struct SpeedAnalyzer {
+    max_speed: f64,
+    speed_stats: SpeedMap,
+}
+
+impl Default for SpeedAnalyzer {
+    fn default() -> Self {
+        Self::with_max_speed(crate::config::AnalysisConfig::default().max_speed_mph)
+    }
+}
+
+impl SpeedAnalyzer {
+    /// Creates an analyzer that ignores trips faster than `max_speed` miles per hour
+    pub fn with_max_speed(max_speed: f64) -> Self {
+        Self {
+            max_speed,
+            speed_stats: HashMap::new(),
+        }
+    }
+}
+
+/// Adds the totals of `source` into `target`
+fn merge_data(target: &mut SpeedData, source: SpeedData) {
+    let (count, distance, hours, speeds) = source;
+    target.0 += count;
+    target.1 += distance;
+    target.2 += hours;
+    for (speed, trips) in speeds {
+        *target.3.entry(speed).or_insert(0) += trips;
+    }
+}
+
+impl BatchAggregator<Vec<SpeedStats>> for SpeedAnalyzer {
+    type Accumulator = SpeedMap;
+
+    /// Process a single batch and accumulate intermediate results
+    /// Filters out trips with non-positive distances or durations, and trips whose
+    /// speed is above the maximum plausible speed
+    /// Accumulates trip count, total distance, total hours and a histogram of speeds
+    /// (in tenths of mph) per pickup zone and hour
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::get_zone_id;
+
+        let max_speed = self.max_speed;
+        let batch_acc = batch
+            .par_iter()
+            .filter(|trip| trip.trip_distance > 0.0 && trip.dropoff_datetime > trip.pickup_datetime)
+            .fold(HashMap::new, |mut acc: SpeedMap, trip| {
+                let hours =
+                    (trip.dropoff_datetime - trip.pickup_datetime).num_seconds() as f64 / 3600.0;
+                let speed = trip.trip_distance / hours;
+                if speed > max_speed {
+                    return acc;
+                }
+
+                let key = (
+                    get_zone_id(trip.pickup_latitude, trip.pickup_longitude),
+                    trip.pickup_datetime.hour(),
+                );
+                let entry = acc.entry(key).or_insert((0, 0.0, 0.0, BTreeMap::new()));
+                entry.0 += 1; // trip count
+                entry.1 += trip.trip_distance; // total distance
+                entry.2 += hours; // total hours
+                *entry.3.entry((speed * 10.0).round() as i64).or_insert(0) += 1; // speed histogram
+                acc
+            })
+            .reduce(HashMap::new, |mut acc1, acc2| {
+                for (key, data) in acc2 {
+                    let entry = acc1.entry(key).or_insert((0, 0.0, 0.0, BTreeMap::new()));
+                    merge_data(entry, data);
+                }
+                acc1
+            });
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for accumulator in accumulators {
+            for (key, data) in accumulator {
+                let entry = self
+                    .speed_stats
+                    .entry(key)
+                    .or_insert((0, 0.0, 0.0, BTreeMap::new()));
+                merge_data(entry, data);
+            }
+        }
+        Ok(())
+    }
+
+    /// Generates the speed stats sorted by zone and hour, comparing each hour with the
+    /// free-flow speed of its zone
+    fn finalize(self) -> Result<Vec<SpeedStats>, ProcessingError> {
+        use crate::utils::{quantile_from_histogram, round_to_2_decimals};
+
+        let mut overnight: HashMap<&str, (f64, f64)> = HashMap::new();
+        for ((zone_id, hour), (_, distance, hours, _)) in &self.speed_stats {
+            if FREE_FLOW_HOURS.contains(hour) {
+                let entry = overnight.entry(zone_id.as_str()).or_insert((0.0, 0.0));
+                entry.0 += distance;
+                entry.1 += hours;
+            }
+        }
+        let free_flow: HashMap<String, f64> = overnight
+            .into_iter()
+            .map(|(zone_id, (distance, hours))| (zone_id.to_string(), distance / hours))
+            .collect();
+
+        let percentile = |speeds: &BTreeMap<i64, usize>, quantile: f64| {
+            quantile_from_histogram(speeds, quantile).unwrap_or(0) as f64 / 10.0
+        };
+
+        let mut results: Vec<SpeedStats> = self
+            .speed_stats
+            .into_iter()
+            .map(|((zone_id, hour), (count, distance, hours, speeds))| {
+                let avg_speed = distance / hours;
+                let free_flow_speed = free_flow.get(&zone_id).copied();
+                SpeedStats {
+                    hour,
+                    trip_count: count,
+                    avg_speed: round_to_2_decimals(avg_speed),
+                    p10_speed: percentile(&speeds, 0.1),
+                    median_speed: percentile(&speeds, 0.5),
+                    p90_speed: percentile(&speeds, 0.9),
+                    free_flow_speed: free_flow_speed.map(round_to_2_decimals),
+                    congestion_index: free_flow_speed
+                        .filter(|_| avg_speed > 0.0)
+                        .map(|speed| round_to_2_decimals(speed / avg_speed)),
+                    zone_id,
+                }
+            })
+            .collect();
+
+        results.sort_by(|a, b| a.zone_id.cmp(&b.zone_id).then(a.hour.cmp(&b.hour)));
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,33 @@
+//! Defines the SpeedStats struct used in speed and congestion analysis.
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpeedStats {
+    /// Pickup zone
+    pub zone_id: String,
+
+    /// Hour of the day (0-23)
+    pub hour: u32,
+
+    /// Number of trips with a plausible speed
+    pub trip_count: usize,
+
+    /// Total distance divided by total travel time, in miles per hour
+    pub avg_speed: f64,
+
+    /// 10th percentile of the trip speeds in miles per hour
+    pub p10_speed: f64,
+
+    /// Median trip speed in miles per hour
+    pub median_speed: f64,
+
+    /// 90th percentile of the trip speeds in miles per hour
+    pub p90_speed: f64,
+
+    /// Average speed of the zone overnight, in miles per hour
+    pub free_flow_speed: Option<f64>,
+
+    /// Free-flow speed divided by the average speed; 1.0 means no congestion and
+    /// 2.0 means trips take twice as long as overnight
+    pub congestion_index: Option<f64>,
+}
@@ -0,0 +1,145 @@
+//! Store-and-forward analysis module
+//!
+//! Calculates the share of trips that were held in the vehicle memory because there
+//! was no connection to the vendor server, per vendor, pickup zone and hour. Compares
+//! the fare and duration of those trips with the ones sent right away, since zones with
+//! high rates point to gaps in cellular coverage.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use chrono::Timelike;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use super::batch_aggregator::BatchAggregator;
+use super::store_forward_stats::StoreForwardStats;
+
+type StoreForwardKey = (i32, String, u32);
+type StoreForwardData = (usize, usize, f64, f64, f64, f64);
+type StoreForwardMap = HashMap<StoreForwardKey, StoreForwardData>;
+
+/// Batch aggregator for store-and-forward analysis
+#[derive(Debug, Default)]
+pub struct StoreForwardAnalyzer {
+    store_forward_stats: StoreForwardMap,
+}
+
+/// Adds the totals of `source` into `target`
+fn merge_data(target: &mut StoreForwardData, source: StoreForwardData) {
+    let (count, stored, live_fare, live_duration, stored_fare, stored_duration) = source;
+    target.0 += count;
+    target.1 += stored;
+    target.2 += live_fare;
+    target.3 += live_duration;
+    target.4 += stored_fare;
+    target.5 += stored_duration;
+}
+
+impl BatchAggregator<Vec<StoreForwardStats>> for StoreForwardAnalyzer {
+    type Accumulator = StoreForwardMap;
+
+    /// Process a single batch and accumulate intermediate results
+    /// Filters out trips with non-positive durations
+    /// Accumulates trip count, store-and-forward trip count, and the total fare and
+    /// duration of the trips sent right away and of the store-and-forward ones per
+    /// vendor, pickup zone and hour
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::get_zone_id;
+
+        let batch_acc = batch
+            .par_iter()
+            .filter(|trip| trip.dropoff_datetime > trip.pickup_datetime)
+            .fold(HashMap::new, |mut acc: StoreForwardMap, trip| {
+                let duration =
+                    (trip.dropoff_datetime - trip.pickup_datetime).num_seconds() as f64 / 60.0;
+                let key = (
+                    trip.vendor_id,
+                    get_zone_id(trip.pickup_latitude, trip.pickup_longitude),
+                    trip.pickup_datetime.hour(),
+                );
+                let entry = acc.entry(key).or_default();
+                entry.0 += 1; // trip count
+                if trip.store_and_fwd_flag.as_deref() == Some("Y") {
+                    entry.1 += 1; // store-and-forward trips
+                    entry.4 += trip.fare_amount;
+                    entry.5 += duration;
+                } else {
+                    entry.2 += trip.fare_amount;
+                    entry.3 += duration;
+                }
+                acc
+            })
+            .reduce(HashMap::new, |mut acc1, acc2| {
+                for (key, data) in acc2 {
+                    merge_data(acc1.entry(key).or_default(), data);
+                }
+                acc1
+            });
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for accumulator in accumulators {
+            for (key, data) in accumulator {
+                merge_data(self.store_forward_stats.entry(key).or_default(), data);
+            }
+        }
+        Ok(())
+    }
+
+    /// Generates the store-and-forward stats sorted by vendor, zone and hour
+    fn finalize(self) -> Result<Vec<StoreForwardStats>, ProcessingError> {
+        use crate::utils::round_to_2_decimals;
+
+        // Averages and differences are only meaningful for the kinds of trips that exist
+        let average = |total: f64, count: usize| (count > 0).then(|| total / count as f64);
+        let difference = |stored: Option<f64>, live: Option<f64>| {
+            stored
+                .zip(live)
+                .map(|(stored, live)| round_to_2_decimals(stored - live))
+        };
+
+        let mut results: Vec<StoreForwardStats> = self
+            .store_forward_stats
+            .into_iter()
+            .map(
+                |(
+                    (vendor_id, zone_id, hour),
+                    (count, stored, live_fare, live_duration, stored_fare, stored_duration),
+                )| {
+                    let live = count - stored;
+                    let avg_fare_live = average(live_fare, live);
+                    let avg_fare_stored = average(stored_fare, stored);
+                    let avg_duration_live = average(live_duration, live);
+                    let avg_duration_stored = average(stored_duration, stored);
+                    StoreForwardStats {
+                        vendor_id,
+                        zone_id,
+                        hour,
+                        trip_count: count,
+                        store_and_forward_trips: stored,
+                        store_and_forward_rate: round_to_2_decimals(
+                            stored as f64 * 100.0 / count as f64,
+                        ),
+                        avg_fare_live: avg_fare_live.map(round_to_2_decimals),
+                        avg_fare_stored: avg_fare_stored.map(round_to_2_decimals),
+                        fare_difference: difference(avg_fare_stored, avg_fare_live),
+                        avg_duration_live: avg_duration_live.map(round_to_2_decimals),
+                        avg_duration_stored: avg_duration_stored.map(round_to_2_decimals),
+                        duration_difference: difference(avg_duration_stored, avg_duration_live),
+                    }
+                },
+            )
+            .collect();
+
+        results.sort_by(|a, b| {
+            (a.vendor_id, &a.zone_id, a.hour).cmp(&(b.vendor_id, &b.zone_id, b.hour))
+        });
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,43 @@
+//! Defines the StoreForwardStats struct used in store-and-forward analysis.
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StoreForwardStats {
+    /// Vendor that provided the records
+    pub vendor_id: i32,
+
+    /// Pickup zone
+    pub zone_id: String,
+
+    /// Hour of the day (0-23) when the trip started
+    pub hour: u32,
+
+    /// Number of trips
+    pub trip_count: usize,
+
+    /// Number of trips held in the vehicle before being sent to the vendor
+    pub store_and_forward_trips: usize,
+
+    /// Percentage of trips held in the vehicle before being sent to the vendor
+    pub store_and_forward_rate: f64,
+
+    /// Average fare of the trips sent right away, if there are any
+    pub avg_fare_live: Option<f64>,
+
+    /// Average fare of the store-and-forward trips, if there are any
+    pub avg_fare_stored: Option<f64>,
+
+    /// Average fare of the store-and-forward trips minus the one of the trips sent
+    /// right away, if the group has both kinds of trips
+    pub fare_difference: Option<f64>,
+
+    /// Average duration in minutes of the trips sent right away, if there are any
+    pub avg_duration_live: Option<f64>,
+
+    /// Average duration in minutes of the store-and-forward trips, if there are any
+    pub avg_duration_stored: Option<f64>,
+
+    /// Average duration of the store-and-forward trips minus the one of the trips sent
+    /// right away, if the group has both kinds of trips
+    pub duration_difference: Option<f64>,
+}
@@ -0,0 +1,170 @@
+//! Tariff analysis module
+//!
+//! Prices every standard rate trip with the tariff in effect on its pickup date and
+//! compares the expected metered fare and surcharges with the amounts charged. Reports
+//! the discrepancies per vendor, pickup zone and hour, counting the trips charged
+//! noticeably more or less than expected.
+use crate::error::ProcessingError;
+use crate::models::{TariffSchedule, TaxiTrip};
+use chrono::Timelike;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use super::batch_aggregator::BatchAggregator;
+use super::tariff_stats::TariffDiscrepancy;
+
+/// Rate code of the trips charged with the metered tariff
+const STANDARD_RATE_CODE: i32 = 1;
+
+/// Smallest difference between the fare charged and the expected one that counts
+/// as an over or undercharge
+const MIN_FARE_TOLERANCE: f64 = 2.5;
+
+/// Ratio of the expected fare used as tolerance when it is above the minimum, since
+/// the expected fare is only an estimate
+const FARE_TOLERANCE_RATIO: f64 = 0.15;
+
+type TariffKey = (i32, String, u32);
+type TariffData = (usize, f64, f64, usize, usize, f64, f64, f64);
+type TariffMap = HashMap<TariffKey, TariffData>;
+
+/// Batch aggregator for tariff analysis
+#[derive(Debug, Default)]
+pub struct TariffAnalyzer {
+    schedule: TariffSchedule,
+    discrepancy_stats: TariffMap,
+}
+
+impl TariffAnalyzer {
+    /// Creates an analyzer that prices trips with the given schedule
+    pub fn with_schedule(schedule: TariffSchedule) -> Self {
+        Self {
+            schedule,
+            discrepancy_stats: HashMap::new(),
+        }
+    }
+}
+
+/// Adds the totals of `source` into `target`
+fn merge_data(target: &mut TariffData, source: TariffData) {
+    let (count, expected, fare, over, under, overcharge, expected_surcharge, extra) = source;
+    target.0 += count;
+    target.1 += expected;
+    target.2 += fare;
+    target.3 += over;
+    target.4 += under;
+    target.5 += overcharge;
+    target.6 += expected_surcharge;
+    target.7 += extra;
+}
+
+impl BatchAggregator<Vec<TariffDiscrepancy>> for TariffAnalyzer {
+    type Accumulator = TariffMap;
+
+    /// Process a single batch and accumulate intermediate results
+    /// Filters out trips that are not standard rate, have non-positive fares, distances
+    /// or durations, or have no tariff in effect on their pickup date
+    /// Accumulates trip count, expected and charged fare, over and undercharged trip
+    /// counts, total overcharge, and expected and charged surcharges per vendor, pickup
+    /// zone and hour
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::get_zone_id;
+
+        let schedule = &self.schedule;
+        let batch_acc = batch
+            .par_iter()
+            .filter(|trip| {
+                trip.rate_code_id == STANDARD_RATE_CODE
+                    && trip.fare_amount > 0.0
+                    && trip.trip_distance > 0.0
+                    && trip.dropoff_datetime > trip.pickup_datetime
+            })
+            .fold(HashMap::new, |mut acc: TariffMap, trip| {
+                let Some(tariff) = schedule.tariff_for(trip.pickup_datetime.date_naive()) else {
+                    return acc;
+                };
+                let expected = tariff.expected_fare(trip);
+                let difference = trip.fare_amount - expected;
+                let tolerance = (expected * FARE_TOLERANCE_RATIO).max(MIN_FARE_TOLERANCE);
+
+                let key = (
+                    trip.vendor_id,
+                    get_zone_id(trip.pickup_latitude, trip.pickup_longitude),
+                    trip.pickup_datetime.hour(),
+                );
+                let entry = acc.entry(key).or_default();
+                entry.0 += 1; // trip count
+                entry.1 += expected; // expected fare
+                entry.2 += trip.fare_amount; // fare charged
+                if difference > tolerance {
+                    entry.3 += 1; // overcharged trips
+                    entry.5 += difference; // total overcharge
+                } else if difference < -tolerance {
+                    entry.4 += 1; // undercharged trips
+                }
+                entry.6 += tariff.expected_surcharge(trip); // expected surcharge
+                entry.7 += trip.extra; // extra charged
+                acc
+            })
+            .reduce(HashMap::new, |mut acc1, acc2| {
+                for (key, data) in acc2 {
+                    merge_data(acc1.entry(key).or_default(), data);
+                }
+                acc1
+            });
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for accumulator in accumulators {
+            for (key, data) in accumulator {
+                merge_data(self.discrepancy_stats.entry(key).or_default(), data);
+            }
+        }
+        Ok(())
+    }
+
+    /// Generates the discrepancies sorted by vendor, zone and hour
+    fn finalize(self) -> Result<Vec<TariffDiscrepancy>, ProcessingError> {
+        use crate::utils::round_to_2_decimals;
+
+        let mut results: Vec<TariffDiscrepancy> = self
+            .discrepancy_stats
+            .into_iter()
+            .map(
+                |(
+                    (vendor_id, zone_id, hour),
+                    (count, expected, fare, over, under, overcharge, expected_surcharge, extra),
+                )| {
+                    let trips = count as f64;
+                    TariffDiscrepancy {
+                        vendor_id,
+                        zone_id,
+                        hour,
+                        trips_compared: count,
+                        avg_expected_fare: round_to_2_decimals(expected / trips),
+                        avg_fare: round_to_2_decimals(fare / trips),
+                        avg_difference: round_to_2_decimals((fare - expected) / trips),
+                        overcharged_trips: over,
+                        undercharged_trips: under,
+                        overcharge_percentage: round_to_2_decimals(over as f64 * 100.0 / trips),
+                        total_overcharge: round_to_2_decimals(overcharge),
+                        avg_expected_surcharge: round_to_2_decimals(expected_surcharge / trips),
+                        avg_extra: round_to_2_decimals(extra / trips),
+                    }
+                },
+            )
+            .collect();
+
+        results.sort_by(|a, b| {
+            (a.vendor_id, &a.zone_id, a.hour).cmp(&(b.vendor_id, &b.zone_id, b.hour))
+        });
+
+        Ok(results)
+    }
+}
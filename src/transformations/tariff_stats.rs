@@ -0,0 +1,44 @@
+//! Defines the TariffDiscrepancy struct used in tariff analysis.
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TariffDiscrepancy {
+    /// Vendor that provided the records
+    pub vendor_id: i32,
+
+    /// Pickup zone
+    pub zone_id: String,
+
+    /// Hour of the day (0-23) when the trip started
+    pub hour: u32,
+
+    /// Number of standard rate trips compared with the tariff
+    pub trips_compared: usize,
+
+    /// Average metered fare expected by the tariff
+    pub avg_expected_fare: f64,
+
+    /// Average fare amount charged
+    pub avg_fare: f64,
+
+    /// Average difference between the fare charged and the expected one
+    pub avg_difference: f64,
+
+    /// Trips charged more than the expected fare plus the tolerance
+    pub overcharged_trips: usize,
+
+    /// Trips charged less than the expected fare minus the tolerance
+    pub undercharged_trips: usize,
+
+    /// Percentage of trips overcharged
+    pub overcharge_percentage: f64,
+
+    /// Total amount charged above the expected fare by the overcharged trips
+    pub total_overcharge: f64,
+
+    /// Average overnight and rush hour surcharge expected by the tariff
+    pub avg_expected_surcharge: f64,
+
+    /// Average extra charged
+    pub avg_extra: f64,
+}
@@ -0,0 +1,74 @@
+//! Defines the structs produced by the time series analysis.
+use crate::models::datetime_format;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimeSeries {
+    /// One point per calendar day with trips, sorted by date
+    pub daily: Vec<DailyPoint>,
+
+    /// One point per 15-minute interval with trips, sorted by start time, when enabled
+    pub quarter_hours: Option<Vec<IntervalPoint>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DailyPoint {
+    pub date: NaiveDate,
+
+    /// Number of trips picked up during the day
+    pub trip_count: usize,
+
+    /// Total revenue
+    pub total_revenue: f64,
+
+    /// Average fare amount
+    pub avg_fare: f64,
+
+    /// Total tips
+    pub total_tips: f64,
+
+    /// Average trips per day over the 7 calendar days ending on this date,
+    /// counting only the days present in the data
+    pub trips_7d_avg: f64,
+
+    /// Average revenue per day over the same 7-day window
+    pub revenue_7d_avg: f64,
+
+    /// Average fare amount of all the trips of the same 7-day window
+    pub avg_fare_7d: f64,
+
+    /// Average tips per day over the same 7-day window
+    pub tips_7d_avg: f64,
+
+    /// Change in trips with respect to the previous day, if it is in the data
+    pub trips_delta: Option<i64>,
+
+    /// Change in revenue with respect to the previous day, if it is in the data
+    pub revenue_delta: Option<f64>,
+
+    /// Change in average fare with respect to the previous day, if it is in the data
+    pub avg_fare_delta: Option<f64>,
+
+    /// Change in tips with respect to the previous day, if it is in the data
+    pub tips_delta: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntervalPoint {
+    /// Start of the 15-minute interval
+    #[serde(with = "datetime_format")]
+    pub start: DateTime<Utc>,
+
+    /// Number of trips picked up during the interval
+    pub trip_count: usize,
+
+    /// Total revenue
+    pub total_revenue: f64,
+
+    /// Average fare amount
+    pub avg_fare: f64,
+
+    /// Total tips
+    pub total_tips: f64,
+}
@@ -0,0 +1,183 @@
+//! Time series analysis module
+//!
+//! Builds a per-calendar-day series of trips, revenue, average fare and tips, along
+//! with 7-day rolling averages and day-over-day deltas. Rolling windows span calendar
+//! days, so datasets covering separate periods (like January 2015 and January to March
+//! 2016) do not get mixed. Optionally, a series of 15-minute intervals is also built.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use chrono::{Duration, NaiveDate, NaiveDateTime, Timelike};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+use super::batch_aggregator::BatchAggregator;
+use super::time_series::{DailyPoint, IntervalPoint, TimeSeries};
+
+/// Number of calendar days of the rolling windows
+const ROLLING_DAYS: i64 = 7;
+
+type SeriesData = (usize, f64, f64, f64);
+type DailyMap = HashMap<NaiveDate, SeriesData>;
+type IntervalMap = HashMap<NaiveDateTime, SeriesData>;
+
+/// Batch aggregator for time series analysis
+#[derive(Debug, Default)]
+pub struct TimeSeriesAnalyzer {
+    quarter_hours: bool,
+    daily_stats: DailyMap,
+    interval_stats: IntervalMap,
+}
+
+impl TimeSeriesAnalyzer {
+    /// Creates an analyzer that also builds the 15-minute series when `quarter_hours` is set
+    pub fn with_quarter_hours(quarter_hours: bool) -> Self {
+        Self {
+            quarter_hours,
+            ..Self::default()
+        }
+    }
+}
+
+/// Returns the start of the 15-minute interval when the trip was picked up
+fn quarter_hour_start(trip: &TaxiTrip) -> NaiveDateTime {
+    let pickup = trip.pickup_datetime.naive_utc();
+    let minute = pickup.minute() / 15 * 15;
+    pickup
+        .date()
+        .and_hms_opt(pickup.hour(), minute, 0)
+        .expect("valid time")
+}
+
+/// Adds a trip to the entry of `key`
+fn add_trip<K: Eq + Hash>(map: &mut HashMap<K, SeriesData>, key: K, trip: &TaxiTrip) {
+    let entry = map.entry(key).or_insert((0, 0.0, 0.0, 0.0));
+    entry.0 += 1; // trip count
+    entry.1 += trip.total_amount; // total revenue
+    entry.2 += trip.fare_amount; // total fare
+    entry.3 += trip.tip_amount; // total tips
+}
+
+/// Adds every entry of `source` into `target`
+fn merge_series<K: Eq + Hash>(target: &mut HashMap<K, SeriesData>, source: HashMap<K, SeriesData>) {
+    for (key, (count, revenue, fare, tips)) in source {
+        let entry = target.entry(key).or_insert((0, 0.0, 0.0, 0.0));
+        entry.0 += count;
+        entry.1 += revenue;
+        entry.2 += fare;
+        entry.3 += tips;
+    }
+}
+
+impl BatchAggregator<TimeSeries> for TimeSeriesAnalyzer {
+    type Accumulator = (DailyMap, IntervalMap);
+
+    /// Process a single batch and accumulate intermediate results
+    /// Filters out trips with non-positive total amounts
+    /// Accumulates trip count, total revenue, total fare and total tips per calendar day
+    /// and, when enabled, per 15-minute interval
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        let quarter_hours = self.quarter_hours;
+        let batch_acc = batch
+            .par_iter()
+            .filter(|trip| trip.total_amount > 0.0)
+            .fold(
+                || (HashMap::new(), HashMap::new()),
+                |(mut daily, mut intervals): Self::Accumulator, trip| {
+                    add_trip(&mut daily, trip.pickup_datetime.date_naive(), trip);
+                    if quarter_hours {
+                        add_trip(&mut intervals, quarter_hour_start(trip), trip);
+                    }
+                    (daily, intervals)
+                },
+            )
+            .reduce(
+                || (HashMap::new(), HashMap::new()),
+                |(mut daily1, mut intervals1), (daily2, intervals2)| {
+                    merge_series(&mut daily1, daily2);
+                    merge_series(&mut intervals1, intervals2);
+                    (daily1, intervals1)
+                },
+            );
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for (daily, intervals) in accumulators {
+            merge_series(&mut self.daily_stats, daily);
+            merge_series(&mut self.interval_stats, intervals);
+        }
+        Ok(())
+    }
+
+    /// Generates the daily series with rolling averages and deltas, and the 15-minute
+    /// series when enabled, both sorted by time
+    fn finalize(self) -> Result<TimeSeries, ProcessingError> {
+        use crate::utils::round_to_2_decimals;
+
+        let days: BTreeMap<NaiveDate, SeriesData> = self.daily_stats.into_iter().collect();
+        let avg_fare = |(count, _, fare, _): &SeriesData| fare / *count as f64;
+
+        let daily = days
+            .iter()
+            .map(|(date, data)| {
+                let (count, revenue, fare, tips) = *data;
+                let window_start = *date - Duration::days(ROLLING_DAYS - 1);
+                let window: Vec<&SeriesData> = days
+                    .range(window_start..=*date)
+                    .map(|(_, data)| data)
+                    .collect();
+                let window_days = window.len() as f64;
+                let window_trips: usize = window.iter().map(|data| data.0).sum();
+                let window_revenue: f64 = window.iter().map(|data| data.1).sum();
+                let window_fare: f64 = window.iter().map(|data| data.2).sum();
+                let window_tips: f64 = window.iter().map(|data| data.3).sum();
+
+                let previous = days.get(&(*date - Duration::days(1)));
+
+                DailyPoint {
+                    date: *date,
+                    trip_count: count,
+                    total_revenue: round_to_2_decimals(revenue),
+                    avg_fare: round_to_2_decimals(fare / count as f64),
+                    total_tips: round_to_2_decimals(tips),
+                    trips_7d_avg: round_to_2_decimals(window_trips as f64 / window_days),
+                    revenue_7d_avg: round_to_2_decimals(window_revenue / window_days),
+                    avg_fare_7d: round_to_2_decimals(window_fare / window_trips as f64),
+                    tips_7d_avg: round_to_2_decimals(window_tips / window_days),
+                    trips_delta: previous.map(|prev| count as i64 - prev.0 as i64),
+                    revenue_delta: previous.map(|prev| round_to_2_decimals(revenue - prev.1)),
+                    avg_fare_delta: previous
+                        .map(|prev| round_to_2_decimals(avg_fare(data) - avg_fare(prev))),
+                    tips_delta: previous.map(|prev| round_to_2_decimals(tips - prev.3)),
+                }
+            })
+            .collect();
+
+        let quarter_hours = self.quarter_hours.then(|| {
+            let mut intervals: Vec<IntervalPoint> = self
+                .interval_stats
+                .into_iter()
+                .map(|(start, (count, revenue, fare, tips))| IntervalPoint {
+                    start: start.and_utc(),
+                    trip_count: count,
+                    total_revenue: round_to_2_decimals(revenue),
+                    avg_fare: round_to_2_decimals(fare / count as f64),
+                    total_tips: round_to_2_decimals(tips),
+                })
+                .collect();
+            intervals.sort_by_key(|point| point.start);
+            intervals
+        });
+
+        Ok(TimeSeries {
+            daily,
+            quarter_hours,
+        })
+    }
+}
@@ -0,0 +1,227 @@
+//! Tip analysis module
+//!
+//! Calculates the distribution of tips as a percentage of the fare by payment type,
+//! hour, pickup zone, distance band and passenger count. Tips paid in cash are not
+//! recorded, so cash trips are counted apart and left out of the distributions instead
+//! of lowering every percentage with their zero tips.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use crate::sketches::QuantileSketch;
+use chrono::Timelike;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use super::batch_aggregator::BatchAggregator;
+use super::tip_stats::{CashTrips, TipAnalysis, TipGroup, TipStats};
+use dimension::TipDimension;
+
+/// Payment type of the trips paid in cash
+const CASH_PAYMENT_TYPE: i32 = 2;
+
+/// Exclusive upper bound in miles and label of each distance band
+const DISTANCE_BANDS: [(f64, &str); 5] = [
+    (1.0, "0-1"),
+    (2.0, "1-2"),
+    (5.0, "2-5"),
+    (10.0, "5-10"),
+    (f64::INFINITY, "10+"),
+];
+
+/// The key has to be public to appear in the accumulator, but the module keeps it
+/// from being named outside this file
+mod dimension {
+    /// Dimension and value shared by a group of trips, ordered by dimension and then
+    /// by value
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    pub enum TipDimension {
+        PaymentType(i32),
+        Hour(u32),
+        Zone(String),
+        /// Index in `DISTANCE_BANDS`
+        DistanceBand(usize),
+        PassengerCount(i32),
+    }
+}
+
+type TipData = (usize, usize, f64, f64, QuantileSketch);
+type TipMap = HashMap<TipDimension, TipData>;
+type TipAccumulator = (TipMap, usize, usize);
+
+/// Batch aggregator for tip analysis
+#[derive(Debug, Default)]
+pub struct TipAnalyzer {
+    tip_stats: TipMap,
+    cash_trips: usize,
+    cash_trips_with_tip: usize,
+}
+
+/// Returns the index in `DISTANCE_BANDS` of the band of the trip distance
+fn distance_band(distance: f64) -> usize {
+    DISTANCE_BANDS
+        .iter()
+        .position(|(upper_bound, _)| distance < *upper_bound)
+        .unwrap_or(DISTANCE_BANDS.len() - 1)
+}
+
+/// Adds the totals and sketch of `source` into `target`
+fn merge_data(target: &mut TipData, source: TipData) {
+    let (count, tipped, tips, tip_percentages, percentages) = source;
+    target.0 += count;
+    target.1 += tipped;
+    target.2 += tips;
+    target.3 += tip_percentages;
+    target.4.merge(&percentages);
+}
+
+/// Builds the tip stats out of the accumulated totals
+fn tip_stats(data: TipData) -> TipStats {
+    use crate::utils::round_to_2_decimals;
+
+    let (count, tipped, tips, tip_percentages, percentages) = data;
+    let trips = count.max(1) as f64;
+    TipStats {
+        trip_count: count,
+        tipped_trip_percentage: round_to_2_decimals(tipped as f64 * 100.0 / trips),
+        avg_tip: round_to_2_decimals(tips / trips),
+        avg_tip_percentage: round_to_2_decimals(tip_percentages / trips),
+        tip_percentage_percentiles: percentages.percentiles(),
+    }
+}
+
+impl BatchAggregator<TipAnalysis> for TipAnalyzer {
+    type Accumulator = TipAccumulator;
+
+    /// Process a single batch and accumulate intermediate results
+    /// Filters out trips with non-positive fares or negative tips
+    /// Counts the cash trips and those with a tip recorded, and for the rest accumulates
+    /// trip count, tipped trips, total tips, sum of tip percentages and a sketch of the
+    /// tip percentages per payment type, hour, zone, distance band and passenger count
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::get_zone_id;
+
+        let batch_acc = batch
+            .par_iter()
+            .filter(|trip| trip.fare_amount > 0.0 && trip.tip_amount >= 0.0)
+            .fold(
+                || (HashMap::new(), 0, 0),
+                |(mut acc, mut cash_trips, mut cash_trips_with_tip): TipAccumulator, trip| {
+                    if trip.payment_type == CASH_PAYMENT_TYPE {
+                        cash_trips += 1;
+                        if trip.tip_amount > 0.0 {
+                            cash_trips_with_tip += 1;
+                        }
+                        return (acc, cash_trips, cash_trips_with_tip);
+                    }
+
+                    let tip_percentage = trip.tip_amount / trip.fare_amount * 100.0;
+                    let keys = [
+                        TipDimension::PaymentType(trip.payment_type),
+                        TipDimension::Hour(trip.pickup_datetime.hour()),
+                        TipDimension::Zone(get_zone_id(
+                            trip.pickup_latitude,
+                            trip.pickup_longitude,
+                        )),
+                        TipDimension::DistanceBand(distance_band(trip.trip_distance)),
+                        TipDimension::PassengerCount(trip.passenger_count.unwrap_or(0).max(0)),
+                    ];
+                    for key in keys {
+                        let entry = acc.entry(key).or_default();
+                        entry.0 += 1; // trip count
+                        if trip.tip_amount > 0.0 {
+                            entry.1 += 1; // tipped trips
+                        }
+                        entry.2 += trip.tip_amount; // total tips
+                        entry.3 += tip_percentage; // sum of tip percentages
+                        entry.4.add(tip_percentage); // tip percentage sketch
+                    }
+                    (acc, cash_trips, cash_trips_with_tip)
+                },
+            )
+            .reduce(
+                || (HashMap::new(), 0, 0),
+                |(mut acc1, cash1, tipped1), (acc2, cash2, tipped2)| {
+                    for (key, data) in acc2 {
+                        merge_data(acc1.entry(key).or_default(), data);
+                    }
+                    (acc1, cash1 + cash2, tipped1 + tipped2)
+                },
+            );
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for (tip_stats, cash_trips, cash_trips_with_tip) in accumulators {
+            for (key, data) in tip_stats {
+                merge_data(self.tip_stats.entry(key).or_default(), data);
+            }
+            self.cash_trips += cash_trips;
+            self.cash_trips_with_tip += cash_trips_with_tip;
+        }
+        Ok(())
+    }
+
+    /// Generates the overall tips, the cash trips and the tips by each dimension
+    fn finalize(self) -> Result<TipAnalysis, ProcessingError> {
+        use crate::utils::round_to_2_decimals;
+
+        // Every trip not paid in cash is in exactly one payment type group
+        let mut totals = TipData::default();
+        for (dimension, data) in &self.tip_stats {
+            if let TipDimension::PaymentType(_) = dimension {
+                merge_data(&mut totals, data.clone());
+            }
+        }
+
+        let all_trips = totals.0 + self.cash_trips;
+        let cash = CashTrips {
+            trip_count: self.cash_trips,
+            trip_percentage: if all_trips > 0 {
+                round_to_2_decimals(self.cash_trips as f64 * 100.0 / all_trips as f64)
+            } else {
+                0.0
+            },
+            trips_with_recorded_tip: self.cash_trips_with_tip,
+        };
+
+        let mut groups: Vec<(TipDimension, TipData)> = self.tip_stats.into_iter().collect();
+        groups.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut analysis = TipAnalysis {
+            summary: tip_stats(totals),
+            cash,
+            by_payment_type: Vec::new(),
+            by_hour: Vec::new(),
+            by_zone: Vec::new(),
+            by_distance_band: Vec::new(),
+            by_passenger_count: Vec::new(),
+        };
+        for (dimension, data) in groups {
+            let (target, group) = match dimension {
+                TipDimension::PaymentType(payment_type) => {
+                    (&mut analysis.by_payment_type, payment_type.to_string())
+                }
+                TipDimension::Hour(hour) => (&mut analysis.by_hour, hour.to_string()),
+                TipDimension::Zone(zone_id) => (&mut analysis.by_zone, zone_id),
+                TipDimension::DistanceBand(band) => (
+                    &mut analysis.by_distance_band,
+                    DISTANCE_BANDS[band].1.to_string(),
+                ),
+                TipDimension::PassengerCount(passenger_count) => (
+                    &mut analysis.by_passenger_count,
+                    passenger_count.to_string(),
+                ),
+            };
+            target.push(TipGroup {
+                group,
+                stats: tip_stats(data),
+            });
+        }
+
+        Ok(analysis)
+    }
+}
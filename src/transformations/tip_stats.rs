@@ -0,0 +1,66 @@
+//! Defines the structs produced by the tip analysis.
+use crate::sketches::Percentiles;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TipAnalysis {
+    /// Tips over all the trips not paid in cash
+    pub summary: TipStats,
+
+    /// Cash trips, left out of every other figure since their tips are not recorded
+    pub cash: CashTrips,
+
+    /// Tips by payment type, sorted by payment type
+    pub by_payment_type: Vec<TipGroup>,
+
+    /// Tips by hour of the day when the trip started, sorted by hour
+    pub by_hour: Vec<TipGroup>,
+
+    /// Tips by pickup zone, sorted by zone
+    pub by_zone: Vec<TipGroup>,
+
+    /// Tips by trip distance band in miles, sorted from the shortest band
+    pub by_distance_band: Vec<TipGroup>,
+
+    /// Tips by passenger count (0 when missing), sorted by passenger count
+    pub by_passenger_count: Vec<TipGroup>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CashTrips {
+    /// Number of trips paid in cash
+    pub trip_count: usize,
+
+    /// Percentage of the trips paid in cash
+    pub trip_percentage: f64,
+
+    /// Number of cash trips that still have a tip recorded
+    pub trips_with_recorded_tip: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TipGroup {
+    /// Value of the dimension shared by the trips of the group
+    pub group: String,
+
+    #[serde(flatten)]
+    pub stats: TipStats,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TipStats {
+    /// Number of trips
+    pub trip_count: usize,
+
+    /// Percentage of trips with a tip
+    pub tipped_trip_percentage: f64,
+
+    /// Average tip amount
+    pub avg_tip: f64,
+
+    /// Average tip as a percentage of the fare
+    pub avg_tip_percentage: f64,
+
+    /// 50th, 90th and 99th percentiles of the tip as a percentage of the fare
+    pub tip_percentage_percentiles: Percentiles,
+}
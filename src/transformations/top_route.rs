@@ -0,0 +1,35 @@
+//! Defines the structs produced by the top routes analysis.
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TopRoutes {
+    /// Number of characters of the geohash cells
+    pub cell_precision: usize,
+
+    /// Number of trips considered
+    pub trips_counted: usize,
+
+    /// Maximum number of trips of any route not listed
+    pub error_bound: u64,
+
+    /// Most frequent routes, from the most to the least frequent
+    pub routes: Vec<RouteCount>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RouteCount {
+    /// Geohash cell where the meter was engaged
+    pub pickup_cell: String,
+
+    /// Geohash cell where the meter was disengaged
+    pub dropoff_cell: String,
+
+    /// Estimated number of trips, never below the true number
+    pub trip_count: u64,
+
+    /// Maximum amount by which `trip_count` exceeds the true number of trips
+    pub max_overcount: u64,
+
+    /// Number of trips the route is guaranteed to have
+    pub min_trip_count: u64,
+}
@@ -0,0 +1,285 @@
+//! Vendor comparison module
+//!
+//! Compares Creative Mobile Technologies (1) and VeriFone (2) per hour of the day and
+//! per calendar day: share of trips, average fare, distance and duration, tip rate,
+//! store-and-forward rate and data-quality rejection rate. A vendor whose meters
+//! misbehave shows up as a higher rejection rate or diverging averages.
+use crate::error::ProcessingError;
+use crate::models::{RunReport, TaxiTrip};
+use chrono::{NaiveDate, Timelike};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::batch_aggregator::BatchAggregator;
+use super::vendor_stats::{VendorAnalysis, VendorDayStats, VendorHourStats, VendorMetrics};
+
+/// Running totals of the trips of a vendor in a period
+#[derive(Debug, Default, Clone)]
+pub struct VendorTotals {
+    trips: usize,
+    fare: f64,
+    distance: f64,
+    duration: f64,
+    card_fare: f64,
+    card_tips: f64,
+    store_and_forward: usize,
+    rejected: usize,
+}
+
+impl VendorTotals {
+    /// Adds a single trip, counting it as rejected if it fails the data-quality checks
+    fn add_trip(&mut self, trip: &TaxiTrip) {
+        use crate::utils::check_data_quality;
+
+        if check_data_quality(trip).is_err() {
+            self.rejected += 1;
+            return;
+        }
+
+        self.trips += 1;
+        self.fare += trip.fare_amount;
+        self.distance += trip.trip_distance;
+        self.duration += (trip.dropoff_datetime - trip.pickup_datetime).num_seconds() as f64 / 60.0;
+        if trip.payment_type == 1 {
+            self.card_fare += trip.fare_amount;
+            self.card_tips += trip.tip_amount;
+        }
+        if trip.store_and_fwd_flag.as_deref() == Some("Y") {
+            self.store_and_forward += 1;
+        }
+    }
+
+    /// Adds the totals of `other` into these ones
+    fn merge(&mut self, other: &VendorTotals) {
+        self.trips += other.trips;
+        self.fare += other.fare;
+        self.distance += other.distance;
+        self.duration += other.duration;
+        self.card_fare += other.card_fare;
+        self.card_tips += other.card_tips;
+        self.store_and_forward += other.store_and_forward;
+        self.rejected += other.rejected;
+    }
+
+    /// Computes the metrics given the amount of trips of all vendors in the period
+    fn metrics(&self, period_trips: usize) -> VendorMetrics {
+        use crate::utils::round_to_2_decimals;
+
+        let ratio = |part: f64, total: f64| {
+            if total > 0.0 {
+                round_to_2_decimals(part / total)
+            } else {
+                0.0
+            }
+        };
+        let trips = self.trips as f64;
+
+        VendorMetrics {
+            trip_count: self.trips,
+            trip_share: ratio(trips * 100.0, period_trips as f64),
+            avg_fare: ratio(self.fare, trips),
+            avg_distance: ratio(self.distance, trips),
+            avg_duration: ratio(self.duration, trips),
+            tip_rate: ratio(self.card_tips * 100.0, self.card_fare),
+            store_and_forward_rate: ratio(self.store_and_forward as f64 * 100.0, trips),
+            rejected_count: self.rejected,
+            rejection_rate: rejection_rate(self.trips, self.rejected),
+        }
+    }
+}
+
+/// Percentage of rejected records over all the records of a vendor in a period
+fn rejection_rate(trips: usize, rejected: usize) -> f64 {
+    use crate::utils::round_to_2_decimals;
+
+    let records = trips + rejected;
+    if records > 0 {
+        round_to_2_decimals(rejected as f64 * 100.0 / records as f64)
+    } else {
+        0.0
+    }
+}
+
+/// Adds `rejected` records discarded by the validation to the metrics
+fn add_rejected(metrics: &mut VendorMetrics, rejected: usize) {
+    metrics.rejected_count += rejected;
+    metrics.rejection_rate = rejection_rate(metrics.trip_count, metrics.rejected_count);
+}
+
+impl VendorAnalysis {
+    /// Adds the records discarded by the validation while reading the input, which
+    /// never reach the analyzer, to the rejections of each vendor and period
+    pub fn include_validation_rejects(&mut self, report: &RunReport) {
+        // Periods where every record of the vendor was discarded get a row of their own
+        let rejected_only = |rejected: usize| {
+            VendorTotals {
+                rejected,
+                ..VendorTotals::default()
+            }
+            .metrics(0)
+        };
+
+        for (&vendor_id, hours) in &report.rejected_by_vendor_hour {
+            for (&hour, &rejected) in hours {
+                match self
+                    .hourly
+                    .iter_mut()
+                    .find(|stats| stats.vendor_id == vendor_id && stats.hour == hour)
+                {
+                    Some(stats) => add_rejected(&mut stats.metrics, rejected),
+                    None => self.hourly.push(VendorHourStats {
+                        vendor_id,
+                        vendor_name: vendor_name(vendor_id).to_string(),
+                        hour,
+                        metrics: rejected_only(rejected),
+                    }),
+                }
+            }
+        }
+        for (&vendor_id, days) in &report.rejected_by_vendor_day {
+            for (&date, &rejected) in days {
+                match self
+                    .daily
+                    .iter_mut()
+                    .find(|stats| stats.vendor_id == vendor_id && stats.date == date)
+                {
+                    Some(stats) => add_rejected(&mut stats.metrics, rejected),
+                    None => self.daily.push(VendorDayStats {
+                        vendor_id,
+                        vendor_name: vendor_name(vendor_id).to_string(),
+                        date,
+                        metrics: rejected_only(rejected),
+                    }),
+                }
+            }
+        }
+
+        self.hourly
+            .sort_by_key(|stats| (stats.hour, stats.vendor_id));
+        self.daily
+            .sort_by_key(|stats| (stats.date, stats.vendor_id));
+    }
+}
+
+type VendorHourMap = HashMap<(i32, u32), VendorTotals>;
+type VendorDayMap = HashMap<(i32, NaiveDate), VendorTotals>;
+
+/// Batch aggregator for vendor comparison
+#[derive(Debug, Default)]
+pub struct VendorAnalyzer {
+    hourly_totals: VendorHourMap,
+    daily_totals: VendorDayMap,
+}
+
+/// Returns the name of the vendor
+fn vendor_name(vendor_id: i32) -> &'static str {
+    match vendor_id {
+        1 => "Creative Mobile Technologies",
+        2 => "VeriFone",
+        _ => "Unknown",
+    }
+}
+
+/// Merges every entry of `source` into `target`
+fn merge_totals<K: Eq + Hash>(
+    target: &mut HashMap<K, VendorTotals>,
+    source: HashMap<K, VendorTotals>,
+) {
+    for (key, totals) in source {
+        target.entry(key).or_default().merge(&totals);
+    }
+}
+
+/// Adds up the accepted trips of all vendors for each period
+fn trips_per_period<P: Eq + Hash + Copy>(
+    totals: &HashMap<(i32, P), VendorTotals>,
+) -> HashMap<P, usize> {
+    let mut period_trips = HashMap::new();
+    for ((_, period), vendor_totals) in totals {
+        *period_trips.entry(*period).or_insert(0) += vendor_totals.trips;
+    }
+    period_trips
+}
+
+impl BatchAggregator<VendorAnalysis> for VendorAnalyzer {
+    type Accumulator = (VendorHourMap, VendorDayMap);
+
+    /// Process a single batch and accumulate intermediate results
+    /// Accumulates the totals of each vendor per hour and per calendar day, counting
+    /// the trips that fail the data-quality checks as rejected
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        let batch_acc = batch
+            .par_iter()
+            .fold(
+                || (HashMap::new(), HashMap::new()),
+                |(mut hourly, mut daily): Self::Accumulator, trip| {
+                    let hour = trip.pickup_datetime.hour();
+                    let date = trip.pickup_datetime.date_naive();
+                    hourly
+                        .entry((trip.vendor_id, hour))
+                        .or_default()
+                        .add_trip(trip);
+                    daily
+                        .entry((trip.vendor_id, date))
+                        .or_default()
+                        .add_trip(trip);
+                    (hourly, daily)
+                },
+            )
+            .reduce(
+                || (HashMap::new(), HashMap::new()),
+                |(mut hourly1, mut daily1), (hourly2, daily2)| {
+                    merge_totals(&mut hourly1, hourly2);
+                    merge_totals(&mut daily1, daily2);
+                    (hourly1, daily1)
+                },
+            );
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for (hourly, daily) in accumulators {
+            merge_totals(&mut self.hourly_totals, hourly);
+            merge_totals(&mut self.daily_totals, daily);
+        }
+        Ok(())
+    }
+
+    /// Generates the hourly and daily metrics sorted by period and vendor
+    fn finalize(self) -> Result<VendorAnalysis, ProcessingError> {
+        let trips_per_hour = trips_per_period(&self.hourly_totals);
+        let trips_per_day = trips_per_period(&self.daily_totals);
+
+        let mut hourly: Vec<VendorHourStats> = self
+            .hourly_totals
+            .iter()
+            .map(|(&(vendor_id, hour), totals)| VendorHourStats {
+                vendor_id,
+                vendor_name: vendor_name(vendor_id).to_string(),
+                hour,
+                metrics: totals.metrics(trips_per_hour[&hour]),
+            })
+            .collect();
+        hourly.sort_by_key(|stats| (stats.hour, stats.vendor_id));
+
+        let mut daily: Vec<VendorDayStats> = self
+            .daily_totals
+            .iter()
+            .map(|(&(vendor_id, date), totals)| VendorDayStats {
+                vendor_id,
+                vendor_name: vendor_name(vendor_id).to_string(),
+                date,
+                metrics: totals.metrics(trips_per_day[&date]),
+            })
+            .collect();
+        daily.sort_by_key(|stats| (stats.date, stats.vendor_id));
+
+        Ok(VendorAnalysis { hourly, daily })
+    }
+}
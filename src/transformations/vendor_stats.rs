@@ -0,0 +1,61 @@
+//! Defines the structs produced by the vendor comparison.
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VendorAnalysis {
+    /// Vendor metrics per hour of the day
+    pub hourly: Vec<VendorHourStats>,
+
+    /// Vendor metrics per calendar day
+    pub daily: Vec<VendorDayStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VendorHourStats {
+    pub vendor_id: i32,
+    pub vendor_name: String,
+    /// Hour of the day (0-23)
+    pub hour: u32,
+    #[serde(flatten)]
+    pub metrics: VendorMetrics,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VendorDayStats {
+    pub vendor_id: i32,
+    pub vendor_name: String,
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub metrics: VendorMetrics,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VendorMetrics {
+    /// Number of trips that passed the data-quality checks
+    pub trip_count: usize,
+
+    /// Percentage of the trips of the period provided by this vendor
+    pub trip_share: f64,
+
+    /// Average fare amount
+    pub avg_fare: f64,
+
+    /// Average trip distance in miles
+    pub avg_distance: f64,
+
+    /// Average trip duration in minutes
+    pub avg_duration: f64,
+
+    /// Tips as a percentage of the fare on credit card trips (cash tips are not recorded)
+    pub tip_rate: f64,
+
+    /// Percentage of trips held in vehicle memory before being sent
+    pub store_and_forward_rate: f64,
+
+    /// Number of records discarded by the validation or the data-quality checks
+    pub rejected_count: usize,
+
+    /// Percentage of records discarded by the validation or the data-quality checks
+    pub rejection_rate: f64,
+}
@@ -0,0 +1,38 @@
+//! Defines the WeekdayHourStats struct used in day of week by hour analysis.
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WeekdayHourStats {
+    /// Day of the week, from Monday (0) to Sunday (6)
+    pub day_of_week: u8,
+
+    /// Name of the day of the week
+    pub day_name: String,
+
+    /// Hour of the day (0-23)
+    pub hour: u32,
+
+    /// Number of trips over the whole dataset
+    pub trip_count: usize,
+
+    /// Total revenue over the whole dataset
+    pub total_revenue: f64,
+
+    /// Average fare amount
+    pub avg_fare: f64,
+
+    /// Average trip duration in minutes
+    pub avg_duration: f64,
+
+    /// Average speed in miles per hour
+    pub avg_speed: f64,
+
+    /// Number of calendar days of this day of the week present in the dataset
+    pub days_observed: usize,
+
+    /// Average number of trips per calendar day observed
+    pub trips_per_day: f64,
+
+    /// Average revenue per calendar day observed
+    pub revenue_per_day: f64,
+}
@@ -0,0 +1,148 @@
+//! Day of week by hour analysis module
+//!
+//! Builds a 7x24 demand matrix, calculating the amount of trips, total revenue, average
+//! fare, average duration and average speed for each day of the week and hour. Totals
+//! are also normalized by the number of calendar days of each day of the week present
+//! in the data, so a month with five Fridays does not skew the results.
+use crate::error::ProcessingError;
+use crate::models::TaxiTrip;
+use chrono::{Datelike, NaiveDate, Timelike};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use super::batch_aggregator::BatchAggregator;
+use super::weekday_hour::WeekdayHourStats;
+
+/// Day names indexed by number of days from Monday
+const DAY_NAMES: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+type WeekdayHourKey = (u8, u32);
+type WeekdayHourData = (usize, f64, f64, f64, f64);
+type WeekdayHourMap = HashMap<WeekdayHourKey, WeekdayHourData>;
+
+/// Batch aggregator for day of week by hour analysis
+#[derive(Debug, Default)]
+pub struct WeekdayHourAnalyzer {
+    cell_stats: WeekdayHourMap,
+    observed_dates: HashSet<NaiveDate>,
+}
+
+impl BatchAggregator<Vec<WeekdayHourStats>> for WeekdayHourAnalyzer {
+    type Accumulator = (WeekdayHourMap, HashSet<NaiveDate>);
+
+    /// Process a single batch and accumulate intermediate results
+    /// Filters out trips with non-positive total amounts, distances or durations
+    /// Accumulates trip count, total revenue, total fare, total duration and total
+    /// distance per day of the week and hour, along with the calendar dates observed
+    fn process_batch(&mut self, batch: &[TaxiTrip]) -> Result<Self::Accumulator, ProcessingError> {
+        use crate::utils::get_day_of_week;
+
+        let batch_acc = batch
+            .par_iter()
+            .filter(|trip| {
+                trip.total_amount > 0.0
+                    && trip.trip_distance > 0.0
+                    && trip.dropoff_datetime > trip.pickup_datetime
+            })
+            .try_fold(
+                || (HashMap::new(), HashSet::new()),
+                |(mut acc, mut dates), trip| {
+                    let key = (get_day_of_week(trip)?, trip.pickup_datetime.hour());
+                    let duration =
+                        (trip.dropoff_datetime - trip.pickup_datetime).num_seconds() as f64 / 60.0;
+                    let entry = acc.entry(key).or_insert((0, 0.0, 0.0, 0.0, 0.0));
+                    entry.0 += 1; // trip count
+                    entry.1 += trip.total_amount; // total revenue
+                    entry.2 += trip.fare_amount; // total fare
+                    entry.3 += duration; // total duration
+                    entry.4 += trip.trip_distance; // total distance
+                    dates.insert(trip.pickup_datetime.date_naive());
+                    Ok::<_, ProcessingError>((acc, dates))
+                },
+            )
+            .try_reduce(
+                || (HashMap::new(), HashSet::new()),
+                |(mut acc1, mut dates1), (acc2, dates2)| {
+                    for (key, (count, revenue, fare, duration, distance)) in acc2 {
+                        let entry = acc1.entry(key).or_insert((0, 0.0, 0.0, 0.0, 0.0));
+                        entry.0 += count;
+                        entry.1 += revenue;
+                        entry.2 += fare;
+                        entry.3 += duration;
+                        entry.4 += distance;
+                    }
+                    dates1.extend(dates2);
+                    Ok((acc1, dates1))
+                },
+            )?;
+
+        Ok(batch_acc)
+    }
+
+    /// Merges multiple accumulators into the main state
+    fn merge_accumulators(
+        &mut self,
+        accumulators: Vec<Self::Accumulator>,
+    ) -> Result<(), ProcessingError> {
+        for (accumulator, dates) in accumulators {
+            for (key, (count, revenue, fare, duration, distance)) in accumulator {
+                let entry = self
+                    .cell_stats
+                    .entry(key)
+                    .or_insert((0, 0.0, 0.0, 0.0, 0.0));
+                entry.0 += count;
+                entry.1 += revenue;
+                entry.2 += fare;
+                entry.3 += duration;
+                entry.4 += distance;
+            }
+            self.observed_dates.extend(dates);
+        }
+        Ok(())
+    }
+
+    /// Generates the matrix cells sorted by day of the week and hour
+    fn finalize(self) -> Result<Vec<WeekdayHourStats>, ProcessingError> {
+        use crate::utils::round_to_2_decimals;
+
+        let mut days_observed = [0usize; 7];
+        for date in &self.observed_dates {
+            days_observed[date.weekday().num_days_from_monday() as usize] += 1;
+        }
+
+        let mut cells: Vec<WeekdayHourStats> = self
+            .cell_stats
+            .into_iter()
+            .map(
+                |((day_of_week, hour), (count, revenue, fare, duration, distance))| {
+                    let days = days_observed[day_of_week as usize].max(1);
+                    WeekdayHourStats {
+                        day_of_week,
+                        day_name: DAY_NAMES[day_of_week as usize].to_string(),
+                        hour,
+                        trip_count: count,
+                        total_revenue: round_to_2_decimals(revenue),
+                        avg_fare: round_to_2_decimals(fare / count as f64),
+                        avg_duration: round_to_2_decimals(duration / count as f64),
+                        avg_speed: round_to_2_decimals(distance / (duration / 60.0)),
+                        days_observed: days,
+                        trips_per_day: round_to_2_decimals(count as f64 / days as f64),
+                        revenue_per_day: round_to_2_decimals(revenue / days as f64),
+                    }
+                },
+            )
+            .collect();
+
+        cells.sort_by_key(|cell| (cell.day_of_week, cell.hour));
+
+        Ok(cells)
+    }
+}
@@ -3,6 +3,7 @@
 use crate::error::ProcessingError;
 use crate::models::TaxiTrip;
 use chrono::{Datelike, NaiveDateTime, Timelike};
+use std::collections::BTreeMap;
 
 /// Parses a datetime string in NYC TLC format.
 ///
@@ -32,56 +33,230 @@ pub fn calculate_trip_duration(trip: &TaxiTrip) -> Result<f64, ProcessingError>
     Ok(duration.num_minutes() as f64)
 }
 
-/// Determines the NYC location zone from latitude and longitude coordinates.
-pub fn get_zone_id(lat: f64, lng: f64) -> String {
-    // Handle invalid coordinates
-    if lat == 0.0 || lng == 0.0 || !(40.0..=41.5).contains(&lat) || !(-75.0..=-73.0).contains(&lng)
-    {
-        return "Unknown".to_string();
+/// Geographic rectangle delimited by minimum and maximum coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_lat: f64,
+    pub max_lat: f64,
+    pub min_lng: f64,
+    pub max_lng: f64,
+}
+
+impl BoundingBox {
+    pub const fn new(min_lat: f64, max_lat: f64, min_lng: f64, max_lng: f64) -> Self {
+        Self {
+            min_lat,
+            max_lat,
+            min_lng,
+            max_lng,
+        }
     }
 
-    // JFK Airport
-    if (40.635..=40.655).contains(&lat) && (-73.795..=-73.755).contains(&lng) {
-        return "JFK_Airport".to_string();
+    /// Checks whether the point lies inside the box, borders included.
+    pub fn contains(&self, lat: f64, lng: f64) -> bool {
+        (self.min_lat..=self.max_lat).contains(&lat) && (self.min_lng..=self.max_lng).contains(&lng)
     }
+}
+
+/// Characters used by the geohash base32 encoding.
+const GEOHASH_BASE32: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
 
-    // LaGuardia Airport
-    if (40.755..=40.785).contains(&lat) && (-73.895..=-73.855).contains(&lng) {
-        return "LaGuardia_Airport".to_string();
+/// NYC zones checked in order by `get_zone_id`. Zones overlap, so the first
+/// matching one wins (airports before boroughs, NYC_Other as catch-all).
+const ZONES: [(&str, BoundingBox); 8] = [
+    (
+        "JFK_Airport",
+        BoundingBox::new(40.635, 40.655, -73.795, -73.755),
+    ),
+    (
+        "LaGuardia_Airport",
+        BoundingBox::new(40.755, 40.785, -73.895, -73.855),
+    ),
+    (
+        "Manhattan",
+        BoundingBox::new(40.695, 40.805, -74.025, -73.895),
+    ),
+    ("Bronx", BoundingBox::new(40.785, 40.925, -73.935, -73.755)),
+    (
+        "Brooklyn",
+        BoundingBox::new(40.565, 40.745, -74.045, -73.825),
+    ),
+    ("Queens", BoundingBox::new(40.535, 40.805, -73.850, -73.695)),
+    (
+        "Staten_Island",
+        BoundingBox::new(40.475, 40.655, -74.265, -74.045),
+    ),
+    ("NYC_Other", BoundingBox::new(40.4, 41.0, -74.3, -73.7)),
+];
+
+/// Checks whether the coordinates are usable, that is, not a placeholder and
+/// within the area surrounding NYC.
+pub fn is_valid_nyc_coordinate(lat: f64, lng: f64) -> bool {
+    lat != 0.0 && lng != 0.0 && (40.0..=41.5).contains(&lat) && (-75.0..=-73.0).contains(&lng)
+}
+
+/// Area considered as NYC when deciding whether a repaired point is plausible.
+const NYC_BOUNDS: BoundingBox = BoundingBox::new(40.4, 41.0, -74.3, -73.7);
+
+/// Attempts to fix coordinates with latitude and longitude swapped or a dropped sign.
+///
+/// Returns the corrected point when the original one is outside the NYC area and
+/// exactly one way of swapping or flipping signs brings it inside. Placeholders
+/// (`0.0`) and ambiguous points are never repaired.
+pub fn repair_coordinates(lat: f64, lng: f64) -> Option<(f64, f64)> {
+    if lat == 0.0 || lng == 0.0 || NYC_BOUNDS.contains(lat, lng) {
+        return None;
     }
 
-    // Manhattan
-    if (40.695..=40.805).contains(&lat) && (-74.025..=-73.895).contains(&lng) {
-        return "Manhattan".to_string();
+    let mut candidates = Vec::new();
+    for (a, b) in [(lat, lng), (lng, lat)] {
+        for (sign_a, sign_b) in [(1.0, 1.0), (1.0, -1.0), (-1.0, 1.0), (-1.0, -1.0)] {
+            let candidate = (a * sign_a, b * sign_b);
+            if candidate != (lat, lng)
+                && NYC_BOUNDS.contains(candidate.0, candidate.1)
+                && !candidates.contains(&candidate)
+            {
+                candidates.push(candidate);
+            }
+        }
     }
 
-    // Bronx
-    if (40.785..=40.925).contains(&lat) && (-73.935..=-73.755).contains(&lng) {
-        return "Bronx".to_string();
+    match candidates.as_slice() {
+        [candidate] => Some(*candidate),
+        _ => None,
     }
+}
+
+/// Repairs the pickup and dropoff coordinates of the trip in place, returning
+/// how many of both points were fixed.
+pub fn repair_trip_coordinates(trip: &mut TaxiTrip) -> usize {
+    let mut repaired = 0;
 
-    // Brooklyn
-    if (40.565..=40.745).contains(&lat) && (-74.045..=-73.825).contains(&lng) {
-        return "Brooklyn".to_string();
+    if let Some((lat, lng)) = repair_coordinates(trip.pickup_latitude, trip.pickup_longitude) {
+        trip.pickup_latitude = lat;
+        trip.pickup_longitude = lng;
+        repaired += 1;
     }
 
-    // Queens
-    if (40.535..=40.805).contains(&lat) && (-73.850..=-73.695).contains(&lng) {
-        return "Queens".to_string();
+    if let Some((lat, lng)) = repair_coordinates(trip.dropoff_latitude, trip.dropoff_longitude) {
+        trip.dropoff_latitude = lat;
+        trip.dropoff_longitude = lng;
+        repaired += 1;
     }
 
-    // Staten Island
-    if (40.475..=40.655).contains(&lat) && (-74.265..=-74.045).contains(&lng) {
-        return "Staten_Island".to_string();
+    repaired
+}
+
+/// Determines the NYC location zone from latitude and longitude coordinates.
+pub fn get_zone_id(lat: f64, lng: f64) -> String {
+    // Handle invalid coordinates
+    if !is_valid_nyc_coordinate(lat, lng) {
+        return "Unknown".to_string();
     }
 
-    // NYC Other
-    if (40.4..=41.0).contains(&lat) && (-74.3..=-73.7).contains(&lng) {
-        return "NYC_Other".to_string();
+    ZONES
+        .iter()
+        .find(|(_, bounds)| bounds.contains(lat, lng))
+        .map_or_else(|| "Unknown".to_string(), |(name, _)| name.to_string())
+}
+
+/// Returns the rectangle used by `get_zone_id` to delimit the given zone.
+///
+/// Zones are approximated by boxes that overlap each other, so the rectangle may
+/// include areas that `get_zone_id` assigns to a zone checked earlier.
+pub fn get_zone_bounds(zone_name: &str) -> Option<BoundingBox> {
+    ZONES
+        .iter()
+        .find(|(name, _)| *name == zone_name)
+        .map(|(_, bounds)| *bounds)
+}
+
+/// Mean Earth radius in miles, used by the haversine formula.
+const EARTH_RADIUS_MILES: f64 = 3958.8;
+
+/// Calculates the great-circle (straight-line) distance in miles between two points.
+pub fn haversine_miles(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
+    let d_lat = (lat2 - lat1).to_radians();
+    let d_lng = (lng2 - lng1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lng / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_MILES * a.sqrt().asin()
+}
+
+/// Encodes the coordinates as a geohash with `precision` characters.
+///
+/// Each extra character splits the cell into 32 smaller ones, so a precision of 6
+/// yields cells of roughly 1.2 km x 0.6 km and a precision of 7 of about 150 m x 150 m.
+pub fn encode_geohash(lat: f64, lng: f64, precision: usize) -> String {
+    let mut lat_range = (-90.0, 90.0);
+    let mut lng_range = (-180.0, 180.0);
+    let mut hash = String::with_capacity(precision);
+    let mut is_lng_bit = true;
+    let mut bits = 0;
+    let mut char_index = 0;
+
+    while hash.len() < precision {
+        let (range, value) = if is_lng_bit {
+            (&mut lng_range, lng)
+        } else {
+            (&mut lat_range, lat)
+        };
+        let mid = (range.0 + range.1) / 2.0;
+        char_index <<= 1;
+        if value >= mid {
+            char_index |= 1;
+            range.0 = mid;
+        } else {
+            range.1 = mid;
+        }
+
+        is_lng_bit = !is_lng_bit;
+        bits += 1;
+        if bits == 5 {
+            hash.push(GEOHASH_BASE32[char_index] as char);
+            bits = 0;
+            char_index = 0;
+        }
     }
 
-    // Outside NYC, Unknown
-    "Unknown".to_string()
+    hash
+}
+
+/// Decodes the area covered by a geohash cell.
+pub fn geohash_bounds(hash: &str) -> Result<BoundingBox, ProcessingError> {
+    let mut lat_range = (-90.0, 90.0);
+    let mut lng_range = (-180.0, 180.0);
+    let mut is_lng_bit = true;
+
+    for c in hash.bytes() {
+        let char_index = GEOHASH_BASE32.iter().position(|&b| b == c).ok_or_else(|| {
+            ProcessingError::Validation {
+                message: format!("Invalid geohash: {}", hash),
+            }
+        })?;
+
+        for shift in (0..5).rev() {
+            let range = if is_lng_bit {
+                &mut lng_range
+            } else {
+                &mut lat_range
+            };
+            let mid = (range.0 + range.1) / 2.0;
+            if (char_index >> shift) & 1 == 1 {
+                range.0 = mid;
+            } else {
+                range.1 = mid;
+            }
+            is_lng_bit = !is_lng_bit;
+        }
+    }
+
+    Ok(BoundingBox {
+        min_lat: lat_range.0,
+        max_lat: lat_range.1,
+        min_lng: lng_range.0,
+        max_lng: lng_range.1,
+    })
 }
 
 /// Determines if a given hour is considered a peak traffic hour.
@@ -94,6 +269,38 @@ pub fn round_to_2_decimals(value: f64) -> f64 {
     (value * 100.0).round() / 100.0
 }
 
+/// Keeps the `limit` items with the smallest keys, in no particular order, once
+/// `items` holds more than twice as many.
+///
+/// Lists of flagged trips call it after every push, so they stay bounded while still
+/// ending up with the same items as if the whole list had been sorted and truncated.
+pub fn keep_smallest<T, K: Ord>(items: &mut Vec<T>, limit: usize, key: impl FnMut(&T) -> K) {
+    if items.len() > limit.saturating_mul(2) {
+        items.select_nth_unstable_by_key(limit, key);
+        items.truncate(limit);
+    }
+}
+
+/// Returns the value at the given quantile (0.0-1.0) of a histogram that maps
+/// each value to the number of times it was observed.
+pub fn quantile_from_histogram(histogram: &BTreeMap<i64, usize>, quantile: f64) -> Option<i64> {
+    let total: usize = histogram.values().sum();
+    if total == 0 {
+        return None;
+    }
+
+    let rank = ((quantile * total as f64).ceil() as usize).clamp(1, total);
+    let mut seen = 0;
+    for (value, count) in histogram {
+        seen += count;
+        if seen >= rank {
+            return Some(*value);
+        }
+    }
+
+    None
+}
+
 /// Validates a taxi trip record for data integrity.
 pub fn validate_trip(trip: &TaxiTrip) -> Result<(), ProcessingError> {
     if trip.trip_distance < 0.0 {
@@ -116,3 +323,37 @@ pub fn validate_trip(trip: &TaxiTrip) -> Result<(), ProcessingError> {
 
     Ok(())
 }
+
+/// Checks whether a valid trip record is consistent enough to be analyzed.
+///
+/// Unlike `validate_trip`, records failing these checks are still read, so analyzers
+/// can report how often each source produces them.
+pub fn check_data_quality(trip: &TaxiTrip) -> Result<(), ProcessingError> {
+    if !is_valid_nyc_coordinate(trip.pickup_latitude, trip.pickup_longitude)
+        || !is_valid_nyc_coordinate(trip.dropoff_latitude, trip.dropoff_longitude)
+    {
+        return Err(ProcessingError::Validation {
+            message: "Coordinates outside the NYC area".to_string(),
+        });
+    }
+
+    if trip.trip_distance <= 0.0 {
+        return Err(ProcessingError::Validation {
+            message: "Trip distance must be positive".to_string(),
+        });
+    }
+
+    if trip.total_amount <= 0.0 {
+        return Err(ProcessingError::Validation {
+            message: "Total amount must be positive".to_string(),
+        });
+    }
+
+    if trip.dropoff_datetime <= trip.pickup_datetime {
+        return Err(ProcessingError::Validation {
+            message: "Dropoff must be after pickup".to_string(),
+        });
+    }
+
+    Ok(())
+}
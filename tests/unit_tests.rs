@@ -22,6 +22,8 @@ fn create_test_trip() -> TaxiTrip {
         tolls_amount: 0.0,
         improvement_surcharge: Some(0.3),
         total_amount: 19.3,
+        pickup_poi: None,
+        dropoff_poi: None,
     }
 }
 
@@ -149,6 +151,7 @@ fn test_is_peak_hour_non_peak() {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn test_round_to_2_decimals() {
     assert_eq!(nyc_taxi_processor::utils::round_to_2_decimals(3.14159), 3.14);
     assert_eq!(nyc_taxi_processor::utils::round_to_2_decimals(10.999), 11.0);
@@ -349,3 +352,1381 @@ fn test_payment_type_cash() {
     trip.payment_type = 2;
     assert_eq!(trip.payment_type, 2);
 }
+
+#[test]
+fn test_quantile_from_histogram() {
+    let histogram = std::collections::BTreeMap::from([(10, 2), (20, 1), (30, 1)]);
+    assert_eq!(
+        nyc_taxi_processor::utils::quantile_from_histogram(&histogram, 0.5),
+        Some(10)
+    );
+    assert_eq!(
+        nyc_taxi_processor::utils::quantile_from_histogram(&histogram, 1.0),
+        Some(30)
+    );
+    assert_eq!(
+        nyc_taxi_processor::utils::quantile_from_histogram(&Default::default(), 0.5),
+        None
+    );
+}
+
+#[test]
+fn test_od_matrix_analyzer_filter_invalid() {
+    let mut analyzer = OdMatrixAnalyzer::default();
+    let mut trip = create_test_trip();
+    trip.dropoff_latitude = 0.0;
+    trip.dropoff_longitude = 0.0;
+
+    let trips = vec![trip];
+    let accumulator = analyzer.process_batch(&trips).unwrap();
+    assert!(accumulator.is_empty());
+}
+
+#[test]
+fn test_od_matrix_finalize() {
+    let mut analyzer = OdMatrixAnalyzer::default();
+    let trip1 = create_test_trip();
+    let mut trip2 = create_test_trip();
+    trip2.dropoff_latitude = 40.645;
+    trip2.dropoff_longitude = -73.78;
+    let mut trip3 = create_test_trip();
+    trip3.dropoff_datetime = Utc.with_ymd_and_hms(2015, 1, 1, 12, 10, 0).unwrap();
+
+    let accumulator1 = analyzer.process_batch(&[trip1, trip2]).unwrap();
+    let accumulator2 = analyzer.process_batch(&[trip3]).unwrap();
+    analyzer
+        .merge_accumulators(vec![accumulator1, accumulator2])
+        .unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].dropoff_zone, "JFK_Airport");
+    assert_eq!(result[1].pickup_zone, "Manhattan");
+    assert_eq!(result[1].dropoff_zone, "Manhattan");
+    assert_eq!(result[1].trip_count, 2);
+    assert_eq!(result[1].median_duration, 10.0);
+}
+
+#[test]
+fn test_encode_geohash() {
+    let hash = nyc_taxi_processor::utils::encode_geohash(57.64911, 10.40744, 11);
+    assert_eq!(hash, "u4pruydqqvj");
+}
+
+#[test]
+fn test_geohash_bounds_contains_point() {
+    let hash = nyc_taxi_processor::utils::encode_geohash(40.75, -73.98, 6);
+    let bounds = nyc_taxi_processor::utils::geohash_bounds(&hash).unwrap();
+    assert!(bounds.min_lat <= 40.75 && 40.75 <= bounds.max_lat);
+    assert!(bounds.min_lng <= -73.98 && -73.98 <= bounds.max_lng);
+    assert!(nyc_taxi_processor::utils::geohash_bounds("invalid!").is_err());
+}
+
+#[test]
+fn test_grid_analyzer_finalize() {
+    let mut analyzer = GridAnalyzer::with_precision(4);
+    let trips = vec![create_test_trip(), create_test_trip()];
+
+    let accumulator = analyzer.process_batch(&trips).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    // Pickup and dropoff fall in the same 4-character cell
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].geohash.len(), 4);
+    assert_eq!(result[0].pickup_count, 2);
+    assert_eq!(result[0].dropoff_count, 2);
+    assert_eq!(result[0].avg_fare, 15.0);
+}
+
+#[test]
+fn test_grid_analyzer_counts_dropoffs_at_dropoff_hour() {
+    let mut analyzer = GridAnalyzer::with_precision(4);
+    let mut trip = create_test_trip();
+    trip.pickup_datetime = Utc.with_ymd_and_hms(2015, 1, 1, 12, 50, 0).unwrap();
+    trip.dropoff_datetime = Utc.with_ymd_and_hms(2015, 1, 1, 13, 10, 0).unwrap();
+
+    let accumulator = analyzer.process_batch(&[trip]).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let mut result = analyzer.finalize().unwrap();
+    result.sort_by_key(|cell| cell.hour);
+
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].hour, 12);
+    assert_eq!(result[0].pickup_count, 1);
+    assert_eq!(result[0].dropoff_count, 0);
+    assert_eq!(result[1].hour, 13);
+    assert_eq!(result[1].pickup_count, 0);
+    assert_eq!(result[1].dropoff_count, 1);
+}
+
+#[test]
+fn test_grid_cell_to_feature() {
+    let mut analyzer = GridAnalyzer::default();
+    let trips = vec![create_test_trip()];
+
+    let accumulator = analyzer.process_batch(&trips).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+    let feature = serde_json::to_value(result[0].to_feature().unwrap()).unwrap();
+
+    assert_eq!(feature["geometry"]["type"], "Polygon");
+    assert_eq!(feature["properties"]["geohash"], result[0].geohash);
+}
+
+#[test]
+fn test_get_zone_bounds() {
+    let bounds = nyc_taxi_processor::utils::get_zone_bounds("JFK_Airport").unwrap();
+    assert!(bounds.contains(40.645, -73.78));
+    assert!(nyc_taxi_processor::utils::get_zone_bounds("Unknown").is_none());
+}
+
+#[test]
+fn test_peak_zone_to_feature() {
+    let mut analyzer = PeakZoneAnalyzer::default();
+    let trips = vec![create_test_trip()];
+
+    let accumulator = analyzer.process_batch(&trips).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    let point = serde_json::to_value(result[0].to_feature(false).unwrap()).unwrap();
+    assert_eq!(point["geometry"]["type"], "Point");
+    assert_eq!(point["geometry"]["coordinates"][0], -73.98);
+    assert_eq!(point["properties"]["zone_name"], "Manhattan");
+
+    let polygon = serde_json::to_value(result[0].to_feature(true).unwrap()).unwrap();
+    assert_eq!(polygon["geometry"]["type"], "Polygon");
+}
+
+#[test]
+fn test_haversine_miles() {
+    let distance = nyc_taxi_processor::utils::haversine_miles(0.0, 0.0, 0.0, 1.0);
+    assert!((distance - 69.09).abs() < 0.01);
+    assert_eq!(
+        nyc_taxi_processor::utils::haversine_miles(40.75, -73.98, 40.75, -73.98),
+        0.0
+    );
+}
+
+#[test]
+fn test_detour_analyzer_finalize() {
+    let mut analyzer = DetourAnalyzer::default();
+    let trip1 = create_test_trip();
+    let mut trip2 = create_test_trip();
+    trip2.trip_distance = 0.2;
+
+    let accumulator = analyzer.process_batch(&[trip1, trip2]).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.stats.len(), 1);
+    assert_eq!(result.stats[0].trip_count, 2);
+    assert_eq!(result.stats[0].flagged_count, 1);
+    assert_eq!(result.stats[0].ratio_distribution["0.00-0.50"], 1);
+    assert_eq!(result.stats[0].ratio_distribution["1.50-2.00"], 1);
+    assert_eq!(result.flagged_trips.len(), 1);
+    assert_eq!(
+        result.flagged_trips[0].reason,
+        "meter_shorter_than_straight_line"
+    );
+}
+
+#[test]
+fn test_detour_analyzer_skips_short_straight_line() {
+    let mut analyzer = DetourAnalyzer::default();
+    let mut trip = create_test_trip();
+    trip.dropoff_latitude = trip.pickup_latitude;
+    trip.dropoff_longitude = trip.pickup_longitude;
+
+    let (accumulator, flags) = analyzer.process_batch(&[trip]).unwrap();
+    assert!(accumulator.is_empty());
+    assert!(flags.is_empty());
+}
+
+#[test]
+fn test_detour_analyzer_caps_flagged_trips() {
+    let trips: Vec<TaxiTrip> = (0..3)
+        .rev()
+        .map(|hour| {
+            let mut trip = create_test_trip();
+            trip.pickup_datetime = Utc.with_ymd_and_hms(2015, 1, 1, hour, 0, 0).unwrap();
+            trip.trip_distance = 0.5;
+            trip
+        })
+        .collect();
+
+    let mut analyzer = DetourAnalyzer::default().with_max_flagged(1);
+    let accumulator = analyzer.process_batch(&trips).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    let flagged: usize = result.stats.iter().map(|stats| stats.flagged_count).sum();
+    assert_eq!(flagged, 3);
+    assert_eq!(result.flagged_trips.len(), 1);
+    assert_eq!(
+        result.flagged_trips[0].pickup_datetime,
+        Utc.with_ymd_and_hms(2015, 1, 1, 0, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_airport_analyzer_finalize() {
+    let mut analyzer = AirportAnalyzer::default();
+    let mut to_jfk = create_test_trip();
+    to_jfk.dropoff_latitude = 40.645;
+    to_jfk.dropoff_longitude = -73.78;
+    to_jfk.rate_code_id = 2;
+    to_jfk.fare_amount = 52.0;
+    let mut to_newark = create_test_trip();
+    to_newark.dropoff_latitude = 0.0;
+    to_newark.dropoff_longitude = 0.0;
+    to_newark.rate_code_id = 3;
+    let not_airport = create_test_trip();
+
+    let accumulator = analyzer
+        .process_batch(&[to_jfk, to_newark, not_airport])
+        .unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.traffic.len(), 2);
+    assert_eq!(result.traffic[0].airport, "JFK");
+    assert_eq!(result.traffic[0].direction, "to");
+    assert_eq!(result.traffic[1].airport, "Newark");
+    assert_eq!(result.jfk_manhattan_trips, 1);
+    assert_eq!(result.flat_fare_violation_count, 0);
+    assert!(result.flat_fare_violations.is_empty());
+}
+
+#[test]
+fn test_airport_analyzer_flat_fare_violation() {
+    let mut analyzer = AirportAnalyzer::default();
+    let mut trip = create_test_trip();
+    trip.pickup_latitude = 40.645;
+    trip.pickup_longitude = -73.78;
+    trip.rate_code_id = 1;
+    trip.fare_amount = 60.0;
+
+    let accumulator = analyzer.process_batch(&[trip]).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.traffic[0].direction, "from");
+    assert_eq!(result.flat_fare_violations.len(), 1);
+    assert_eq!(result.flat_fare_violations[0].route, "JFK->Manhattan");
+    assert_eq!(result.flat_fare_violations[0].fare_amount, 60.0);
+}
+
+#[test]
+fn test_airport_analyzer_skips_reversed_trips() {
+    let mut analyzer = AirportAnalyzer::default();
+    let mut to_jfk = create_test_trip();
+    to_jfk.dropoff_latitude = 40.645;
+    to_jfk.dropoff_longitude = -73.78;
+    to_jfk.dropoff_datetime = Utc.with_ymd_and_hms(2015, 1, 1, 12, 45, 30).unwrap();
+    let mut reversed = to_jfk.clone();
+    reversed.dropoff_datetime = Utc.with_ymd_and_hms(2015, 1, 1, 11, 0, 0).unwrap();
+
+    let accumulator = analyzer.process_batch(&[to_jfk, reversed]).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.traffic.len(), 1);
+    assert_eq!(result.traffic[0].trip_count, 1);
+    assert_eq!(result.traffic[0].avg_duration, 45.5);
+}
+
+#[test]
+fn test_airport_analyzer_caps_flat_fare_violations() {
+    let trips: Vec<TaxiTrip> = (0..3)
+        .rev()
+        .map(|hour| {
+            let mut trip = create_test_trip();
+            trip.pickup_datetime = Utc.with_ymd_and_hms(2015, 1, 1, hour, 0, 0).unwrap();
+            trip.dropoff_datetime = Utc.with_ymd_and_hms(2015, 1, 1, hour, 45, 0).unwrap();
+            trip.pickup_latitude = 40.645;
+            trip.pickup_longitude = -73.78;
+            trip
+        })
+        .collect();
+
+    let mut analyzer = AirportAnalyzer::default().with_max_flagged(1);
+    let accumulator = analyzer.process_batch(&trips).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.flat_fare_violation_count, 3);
+    assert_eq!(result.flat_fare_violations_listed, 1);
+    assert_eq!(result.flat_fare_violations.len(), 1);
+    assert_eq!(
+        result.flat_fare_violations[0].pickup_datetime,
+        Utc.with_ymd_and_hms(2015, 1, 1, 0, 0, 0).unwrap()
+    );
+}
+
+fn create_test_poi(name: &str, latitude: f64, longitude: f64) -> nyc_taxi_processor::models::Poi {
+    nyc_taxi_processor::models::Poi {
+        name: name.to_string(),
+        latitude,
+        longitude,
+        category: None,
+    }
+}
+
+#[test]
+fn test_poi_index_nearest_within() {
+    let index = PoiIndex::new(vec![
+        create_test_poi("Penn Station", 40.7506, -73.9935),
+        create_test_poi("Grand Central", 40.7527, -73.9772),
+        create_test_poi("Times Square", 40.7580, -73.9855),
+    ]);
+
+    let nearest = index.nearest_within(40.7505, -73.9930, 100.0).unwrap();
+    assert_eq!(nearest.name, "Penn Station");
+    assert!(index.nearest_within(40.70, -74.00, 100.0).is_none());
+}
+
+#[test]
+fn test_poi_index_matches_brute_force() {
+    let pois: Vec<_> = (0..200)
+        .map(|i| {
+            let lat = 40.70 + (i * 37 % 200) as f64 * 0.0005;
+            let lng = -74.00 + (i * 91 % 200) as f64 * 0.0005;
+            create_test_poi(&format!("poi_{}", i), lat, lng)
+        })
+        .collect();
+    let index = PoiIndex::new(pois.clone());
+
+    for step in 0..50 {
+        let lat = 40.70 + step as f64 * 0.0019;
+        let lng = -74.00 + step as f64 * 0.0017;
+        let expected = pois
+            .iter()
+            .map(|poi| {
+                let distance = nyc_taxi_processor::utils::haversine_miles(
+                    lat,
+                    lng,
+                    poi.latitude,
+                    poi.longitude,
+                );
+                (distance, &poi.name)
+            })
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+            .unwrap();
+        let nearest = index.nearest_within(lat, lng, 10_000.0).unwrap();
+        assert_eq!(&nearest.name, expected.1);
+    }
+}
+
+#[test]
+fn test_poi_analyzer_finalize() {
+    let index = PoiIndex::new(vec![create_test_poi("Penn Station", 40.7506, -73.9935)]);
+    let mut tagged = create_test_trip();
+    tagged.pickup_latitude = 40.7507;
+    tagged.pickup_longitude = -73.9936;
+    index.enrich_trip(&mut tagged, 100.0);
+    let mut untagged = create_test_trip();
+    index.enrich_trip(&mut untagged, 100.0);
+
+    assert_eq!(tagged.pickup_poi.as_deref(), Some("Penn Station"));
+    assert!(tagged.dropoff_poi.is_none());
+    assert!(untagged.pickup_poi.is_none());
+
+    let mut analyzer = PoiAnalyzer::default();
+    let accumulator = analyzer.process_batch(&[tagged, untagged]).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].poi_name, "Penn Station");
+    assert_eq!(result[0].pickup_count, 1);
+    assert_eq!(result[0].dropoff_count, 0);
+}
+
+#[test]
+fn test_repair_coordinates_swapped_and_sign_flipped() {
+    use nyc_taxi_processor::utils::repair_coordinates;
+
+    assert_eq!(repair_coordinates(-73.98, 40.75), Some((40.75, -73.98)));
+    assert_eq!(repair_coordinates(40.75, 73.98), Some((40.75, -73.98)));
+    assert_eq!(repair_coordinates(73.98, 40.75), Some((40.75, -73.98)));
+}
+
+#[test]
+fn test_repair_coordinates_leaves_valid_and_placeholders() {
+    use nyc_taxi_processor::utils::repair_coordinates;
+
+    assert_eq!(repair_coordinates(40.75, -73.98), None);
+    assert_eq!(repair_coordinates(0.0, 0.0), None);
+    assert_eq!(repair_coordinates(0.0, -73.98), None);
+    assert_eq!(repair_coordinates(50.0, -80.0), None);
+}
+
+#[test]
+fn test_process_in_batches_run_report() {
+    let path = std::env::temp_dir().join("nyc_taxi_processor_run_report_test.csv");
+    let header = "VendorID,tpep_pickup_datetime,tpep_dropoff_datetime,passenger_count,\
+                  trip_distance,pickup_longitude,pickup_latitude,RateCodeID,store_and_fwd_flag,\
+                  dropoff_longitude,dropoff_latitude,payment_type,fare_amount,extra,mta_tax,\
+                  tip_amount,tolls_amount,improvement_surcharge,total_amount";
+    let rows = [
+        // Valid trip
+        "1,2015-01-01 12:00:00,2015-01-01 12:30:00,1,5.0,-73.98,40.75,1,N,-73.95,40.78,1,15,0.5,0.5,3,0,0.3,19.3",
+        // Swapped pickup coordinates
+        "2,2015-01-01 12:00:00,2015-01-01 12:30:00,1,5.0,40.75,-73.98,1,N,-73.95,40.78,1,15,0.5,0.5,3,0,0.3,19.3",
+        // Placeholder dropoff coordinates
+        "1,2015-01-01 12:00:00,2015-01-01 12:30:00,1,5.0,-73.98,40.75,1,N,0,0,1,15,0.5,0.5,3,0,0.3,19.3",
+        // Invalid vendor
+        "7,2015-01-01 12:00:00,2015-01-01 12:30:00,1,5.0,-73.98,40.75,1,N,-73.95,40.78,1,15,0.5,0.5,3,0,0.3,19.3",
+    ];
+    std::fs::write(&path, format!("{}\n{}\n", header, rows.join("\n"))).unwrap();
+
+    let config = AnalysisConfig {
+        repair_coordinates: true,
+        ..AnalysisConfig::default()
+    };
+    let processor = TaxiProcessor::with_config(10, config);
+    let mut trips = Vec::new();
+    let report = processor
+        .process_in_batches(path.to_str().unwrap(), |batch| {
+            trips.extend_from_slice(batch);
+            Ok(())
+        })
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(report.records_read, 4);
+    assert_eq!(report.records_rejected, 1);
+    assert_eq!(report.coordinates_repaired, 1);
+    assert_eq!(report.invalid_coordinates, 1);
+    assert_eq!(trips.len(), 3);
+    assert_eq!(trips[1].pickup_latitude, 40.75);
+    assert_eq!(trips[1].pickup_longitude, -73.98);
+}
+
+#[test]
+fn test_process_in_batches_enriches_every_batch() {
+    let path = std::env::temp_dir().join("nyc_taxi_processor_poi_batches_test.csv");
+    let header = "VendorID,tpep_pickup_datetime,tpep_dropoff_datetime,passenger_count,\
+                  trip_distance,pickup_longitude,pickup_latitude,RateCodeID,store_and_fwd_flag,\
+                  dropoff_longitude,dropoff_latitude,payment_type,fare_amount,extra,mta_tax,\
+                  tip_amount,tolls_amount,improvement_surcharge,total_amount";
+    let row = "1,2015-01-01 12:00:00,2015-01-01 12:30:00,1,5.0,-73.98,40.75,1,N,-73.95,40.78,1,15,0.5,0.5,3,0,0.3,19.3";
+    std::fs::write(&path, format!("{}\n{}\n{}\n{}\n", header, row, row, row)).unwrap();
+
+    let index = PoiIndex::new(vec![create_test_poi("Times Square", 40.7501, -73.9801)]);
+    let processor = TaxiProcessor::with_config(2, AnalysisConfig::default()).with_poi_index(index);
+    let mut batch_sizes = Vec::new();
+    let mut trips = Vec::new();
+    processor
+        .process_in_batches(path.to_str().unwrap(), |batch| {
+            batch_sizes.push(batch.len());
+            trips.extend_from_slice(batch);
+            Ok(())
+        })
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(batch_sizes, vec![2, 1]);
+    assert!(trips
+        .iter()
+        .all(|trip| trip.pickup_poi.as_deref() == Some("Times Square")));
+    assert!(trips.iter().all(|trip| trip.dropoff_poi.is_none()));
+}
+
+#[test]
+fn test_hourly_weekday_weekend_split() {
+    let mut analyzer = HourlyPatternAnalyzer::default();
+    let mut weekday = create_test_trip();
+    weekday.pickup_datetime = Utc.with_ymd_and_hms(2015, 1, 2, 18, 0, 0).unwrap();
+    weekday.dropoff_datetime = Utc.with_ymd_and_hms(2015, 1, 2, 18, 30, 0).unwrap();
+    let mut weekend = create_test_trip();
+    weekend.pickup_datetime = Utc.with_ymd_and_hms(2015, 1, 3, 18, 0, 0).unwrap();
+    weekend.dropoff_datetime = Utc.with_ymd_and_hms(2015, 1, 3, 18, 30, 0).unwrap();
+
+    let accumulator = analyzer.process_batch(&[weekday, weekend]).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].day_type, "weekday");
+    assert!(result[0].is_peak_hour);
+    assert_eq!(result[1].day_type, "weekend");
+    assert!(!result[1].is_peak_hour);
+}
+
+#[test]
+fn test_hourly_tips() {
+    let mut analyzer = HourlyPatternAnalyzer::default();
+    let trip1 = create_test_trip();
+    let mut trip2 = create_test_trip();
+    trip2.tip_amount = 0.0;
+
+    let accumulator = analyzer.process_batch(&[trip1, trip2]).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].total_tips, 3.0);
+    assert_eq!(result[0].avg_tip, 1.5);
+    assert_eq!(result[0].tip_percentage, 10.0);
+}
+
+#[test]
+fn test_weekday_hour_normalized_per_day() {
+    let mut analyzer = WeekdayHourAnalyzer::default();
+    // Two Thursdays, the second one with two trips
+    let trip1 = create_test_trip();
+    let mut trip2 = create_test_trip();
+    trip2.pickup_datetime = Utc.with_ymd_and_hms(2015, 1, 8, 12, 0, 0).unwrap();
+    trip2.dropoff_datetime = Utc.with_ymd_and_hms(2015, 1, 8, 12, 30, 0).unwrap();
+    let trip3 = trip2.clone();
+
+    let accumulator1 = analyzer.process_batch(&[trip1]).unwrap();
+    let accumulator2 = analyzer.process_batch(&[trip2, trip3]).unwrap();
+    analyzer
+        .merge_accumulators(vec![accumulator1, accumulator2])
+        .unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].day_of_week, 3);
+    assert_eq!(result[0].day_name, "Thursday");
+    assert_eq!(result[0].trip_count, 3);
+    assert_eq!(result[0].days_observed, 2);
+    assert_eq!(result[0].trips_per_day, 1.5);
+    assert_eq!(result[0].avg_speed, 10.0);
+}
+
+#[test]
+fn test_check_data_quality() {
+    let trip = create_test_trip();
+    assert!(nyc_taxi_processor::utils::check_data_quality(&trip).is_ok());
+
+    let mut zero_distance = create_test_trip();
+    zero_distance.trip_distance = 0.0;
+    assert!(nyc_taxi_processor::utils::check_data_quality(&zero_distance).is_err());
+
+    let mut reversed = create_test_trip();
+    reversed.dropoff_datetime = reversed.pickup_datetime;
+    assert!(nyc_taxi_processor::utils::check_data_quality(&reversed).is_err());
+}
+
+#[test]
+fn test_vendor_analyzer_finalize() {
+    let mut analyzer = VendorAnalyzer::default();
+    let trip1 = create_test_trip();
+    let mut trip2 = create_test_trip();
+    trip2.vendor_id = 2;
+    trip2.store_and_fwd_flag = Some("Y".to_string());
+    let mut trip3 = create_test_trip();
+    trip3.vendor_id = 2;
+    trip3.pickup_latitude = 0.0;
+
+    let accumulator = analyzer.process_batch(&[trip1, trip2, trip3]).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.hourly.len(), 2);
+    assert_eq!(result.daily.len(), 2);
+
+    let verifone = &result.hourly[1];
+    assert_eq!(verifone.vendor_name, "VeriFone");
+    assert_eq!(verifone.metrics.trip_count, 1);
+    assert_eq!(verifone.metrics.trip_share, 50.0);
+    assert_eq!(verifone.metrics.store_and_forward_rate, 100.0);
+    assert_eq!(verifone.metrics.rejection_rate, 50.0);
+    assert_eq!(verifone.metrics.tip_rate, 20.0);
+    assert_eq!(result.daily[0].metrics.rejection_rate, 0.0);
+}
+
+#[test]
+fn test_vendor_analysis_includes_validation_rejects() {
+    let mut analyzer = VendorAnalyzer::default();
+    let accumulator = analyzer.process_batch(&[create_test_trip()]).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let mut result = analyzer.finalize().unwrap();
+
+    let mut negative_distance = create_test_trip();
+    negative_distance.trip_distance = -1.0;
+    let mut negative_total = create_test_trip();
+    negative_total.vendor_id = 2;
+    negative_total.total_amount = -19.3;
+    let mut invalid_vendor = create_test_trip();
+    invalid_vendor.vendor_id = 7;
+
+    let mut report = nyc_taxi_processor::models::RunReport::default();
+    for trip in [&negative_distance, &negative_total, &invalid_vendor] {
+        report.add_rejected(trip);
+    }
+    assert_eq!(report.records_rejected, 3);
+    result.include_validation_rejects(&report);
+
+    assert_eq!(result.hourly.len(), 2);
+    let cmt = &result.hourly[0];
+    assert_eq!(cmt.metrics.trip_count, 1);
+    assert_eq!(cmt.metrics.rejected_count, 1);
+    assert_eq!(cmt.metrics.rejection_rate, 50.0);
+    let verifone = &result.hourly[1];
+    assert_eq!(verifone.metrics.trip_count, 0);
+    assert_eq!(verifone.metrics.rejection_rate, 100.0);
+
+    assert_eq!(result.daily.len(), 2);
+    assert_eq!(result.daily[1].metrics.rejected_count, 1);
+}
+
+#[test]
+fn test_rate_code_analyzer_finalize() {
+    let mut analyzer = RateCodeAnalyzer::default();
+    let trip1 = create_test_trip();
+    let mut trip2 = create_test_trip();
+    trip2.rate_code_id = 5;
+    trip2.fare_amount = 30.0;
+    let mut trip3 = create_test_trip();
+    trip3.rate_code_id = 2;
+
+    let accumulator = analyzer.process_batch(&[trip1, trip2, trip3]).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.rate_codes.len(), 3);
+    assert_eq!(result.rate_codes[0].rate_code_name, "Standard rate");
+    assert_eq!(result.rate_codes[1].rate_code_name, "JFK");
+    assert_eq!(result.rate_codes[2].rate_code_name, "Negotiated fare");
+    assert_eq!(result.rate_codes[2].trip_count, 1);
+
+    assert_eq!(result.negotiated_fares.len(), 1);
+    let usage = &result.negotiated_fares[0];
+    assert_eq!(usage.negotiated_trips, 1);
+    assert_eq!(usage.total_trips, 3);
+    assert_eq!(usage.negotiated_percentage, 33.33);
+    assert_eq!(usage.avg_negotiated_fare, 30.0);
+}
+
+#[test]
+fn test_occupancy_analyzer_finalize() {
+    let mut analyzer = OccupancyAnalyzer::default();
+    let mut trip1 = create_test_trip();
+    trip1.passenger_count = Some(2);
+    let mut trip2 = create_test_trip();
+    trip2.passenger_count = None;
+    let mut trip3 = create_test_trip();
+    trip3.passenger_count = Some(0);
+    let mut trip4 = create_test_trip();
+    trip4.passenger_count = Some(1);
+
+    let accumulator = analyzer
+        .process_batch(&[trip1, trip2, trip3, trip4])
+        .unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.by_zone_hour.len(), 1);
+    let summary = &result.summary;
+    assert_eq!(summary.trip_count, 4);
+    assert_eq!(summary.distribution.get(&0), Some(&2));
+    assert_eq!(summary.distribution.get(&2), Some(&1));
+    assert_eq!(summary.missing_or_zero_percentage, 50.0);
+    assert_eq!(summary.avg_passengers, 1.5);
+    assert_eq!(summary.passenger_miles, 15.0);
+    assert_eq!(summary.revenue_per_passenger, 12.87);
+}
+
+#[test]
+fn test_speed_analyzer_finalize() {
+    let mut analyzer = SpeedAnalyzer::with_max_speed(50.0);
+    // 5 miles in 30 minutes at noon
+    let day_trip = create_test_trip();
+    // 5 miles in 15 minutes at 2 AM
+    let mut night_trip = create_test_trip();
+    night_trip.pickup_datetime -= chrono::Duration::hours(10);
+    night_trip.dropoff_datetime = night_trip.pickup_datetime + chrono::Duration::minutes(15);
+    // 5 miles in 1 minute is implausible
+    let mut fast_trip = create_test_trip();
+    fast_trip.dropoff_datetime = fast_trip.pickup_datetime + chrono::Duration::minutes(1);
+
+    let accumulator = analyzer
+        .process_batch(&[day_trip, night_trip, fast_trip])
+        .unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.len(), 2);
+    let night = &result[0];
+    assert_eq!(night.hour, 2);
+    assert_eq!(night.avg_speed, 20.0);
+    assert_eq!(night.congestion_index, Some(1.0));
+
+    let day = &result[1];
+    assert_eq!(day.hour, 12);
+    assert_eq!(day.trip_count, 1);
+    assert_eq!(day.avg_speed, 10.0);
+    assert_eq!(day.median_speed, 10.0);
+    assert_eq!(day.free_flow_speed, Some(20.0));
+    assert_eq!(day.congestion_index, Some(2.0));
+}
+
+#[test]
+fn test_quantile_sketch_accuracy() {
+    let mut sketch = QuantileSketch::new();
+    assert_eq!(sketch.quantile(0.5), None);
+
+    for value in 1..=1000 {
+        sketch.add(value as f64);
+    }
+
+    assert_eq!(sketch.count(), 1000);
+    for (quantile, expected) in [(0.5, 500.0), (0.9, 900.0), (0.99, 990.0)] {
+        let estimate = sketch.quantile(quantile).unwrap();
+        assert!((estimate - expected).abs() / expected <= 0.02);
+    }
+}
+
+#[test]
+fn test_quantile_sketch_merge() {
+    let mut whole = QuantileSketch::new();
+    let mut first = QuantileSketch::new();
+    let mut second = QuantileSketch::new();
+
+    for value in [-3.0, 0.0, 2.5, 8.0, 52.0, 300.0] {
+        whole.add(value);
+        if value < 5.0 {
+            first.add(value);
+        } else {
+            second.add(value);
+        }
+    }
+    first.merge(&second);
+
+    assert_eq!(first.count(), whole.count());
+    assert_eq!(first.percentiles(), whole.percentiles());
+    assert!(first.quantile(0.0).unwrap() < 0.0);
+    assert_eq!(first.quantile(0.2), Some(0.0));
+}
+
+#[test]
+fn test_payment_analyzer_percentiles_across_batches() {
+    let mut analyzer = PaymentAnalyzer::default();
+    let trips: Vec<TaxiTrip> = (1..=10)
+        .map(|i| {
+            let mut trip = create_test_trip();
+            trip.total_amount = i as f64 * 10.0;
+            trip
+        })
+        .collect();
+
+    let first = analyzer.process_batch(&trips[..4]).unwrap();
+    let second = analyzer.process_batch(&trips[4..]).unwrap();
+    analyzer.merge_accumulators(vec![first, second]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    let percentiles = &result[0].amount_percentiles;
+    assert!((percentiles.p50 - 50.0).abs() <= 1.0);
+    assert!((percentiles.p99 - 90.0).abs() <= 1.0);
+}
+
+#[test]
+fn test_moments_merge_matches_single_pass() {
+    let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+
+    let mut whole = Moments::new();
+    values.iter().for_each(|value| whole.add(*value));
+
+    let mut first = Moments::new();
+    let mut second = Moments::new();
+    values[..3].iter().for_each(|value| first.add(*value));
+    values[3..].iter().for_each(|value| second.add(*value));
+    first.merge(&second);
+    first.merge(&Moments::new());
+
+    assert_eq!(first.count(), 8);
+    assert_eq!(first.mean(), Some(5.0));
+    assert!((first.variance().unwrap() - 32.0 / 7.0).abs() < 1e-12);
+    assert!((first.variance().unwrap() - whole.variance().unwrap()).abs() < 1e-12);
+    assert_eq!(first.min(), Some(2.0));
+    assert_eq!(first.max(), Some(9.0));
+}
+
+#[test]
+fn test_moments_empty_and_single_value() {
+    let mut moments = Moments::new();
+    assert_eq!(moments.mean(), None);
+    assert_eq!(
+        moments.spread(),
+        Spread {
+            std_dev: 0.0,
+            min: 0.0,
+            max: 0.0
+        }
+    );
+
+    moments.add(12.5);
+    assert_eq!(moments.std_dev(), None);
+    assert_eq!(moments.min(), Some(12.5));
+    assert_eq!(moments.max(), Some(12.5));
+}
+
+#[test]
+fn test_hourly_spread_independent_of_batches() {
+    let trips: Vec<TaxiTrip> = (1..=6)
+        .map(|i| {
+            let mut trip = create_test_trip();
+            trip.fare_amount = i as f64 * 3.0;
+            trip
+        })
+        .collect();
+
+    let mut single = HourlyPatternAnalyzer::default();
+    let accumulator = single.process_batch(&trips).unwrap();
+    single.merge_accumulators(vec![accumulator]).unwrap();
+    let single = single.finalize().unwrap();
+
+    let mut split = HourlyPatternAnalyzer::default();
+    let accumulators = trips
+        .chunks(2)
+        .map(|batch| split.process_batch(batch).unwrap())
+        .collect();
+    split.merge_accumulators(accumulators).unwrap();
+    let split = split.finalize().unwrap();
+
+    assert_eq!(single[0].fare_spread, split[0].fare_spread);
+    assert_eq!(split[0].fare_spread.min, 3.0);
+    assert_eq!(split[0].fare_spread.max, 18.0);
+    assert_eq!(split[0].fare_spread.std_dev, 5.61);
+    assert_eq!(split[0].distance_spread.std_dev, 0.0);
+}
+
+#[test]
+fn test_hyperloglog_estimate() {
+    let mut small = HyperLogLog::new();
+    assert_eq!(small.estimate(), 0);
+    for value in ["a", "b", "c", "a", "b"] {
+        small.add(value);
+    }
+    assert_eq!(small.estimate(), 3);
+    assert!(!small.is_dense());
+
+    let mut large = HyperLogLog::new();
+    for value in 0..100_000u32 {
+        large.add(&value);
+    }
+    assert!(large.is_dense());
+    let error = (large.estimate() as f64 - 100_000.0).abs() / 100_000.0;
+    assert!(error < 0.05);
+}
+
+#[test]
+fn test_hyperloglog_merge() {
+    let mut whole = HyperLogLog::new();
+    let mut sparse = HyperLogLog::new();
+    let mut dense = HyperLogLog::new();
+
+    for value in 0..5_000u32 {
+        whole.add(&value);
+        if value < 100 {
+            sparse.add(&value);
+        } else {
+            dense.add(&value);
+        }
+    }
+    // Overlapping values must not be counted twice
+    for value in 0..50u32 {
+        dense.add(&value);
+    }
+
+    let mut merged = sparse.clone();
+    merged.merge(&dense);
+    assert_eq!(merged.estimate(), whole.estimate());
+
+    dense.merge(&sparse);
+    assert_eq!(dense.estimate(), whole.estimate());
+}
+
+#[test]
+fn test_peak_zone_distinct_counts() {
+    let mut analyzer = PeakZoneAnalyzer::with_cell_precision(7);
+    let trip1 = create_test_trip();
+    let mut trip2 = create_test_trip();
+    trip2.pickup_latitude += 0.01;
+    let mut trip3 = create_test_trip();
+    trip3.pickup_datetime += chrono::Duration::days(1);
+
+    let first = analyzer.process_batch(&[trip1, trip2]).unwrap();
+    let second = analyzer.process_batch(&[trip3]).unwrap();
+    analyzer.merge_accumulators(vec![first, second]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].distinct_pickup_cells, 2);
+    assert_eq!(result[0].active_days, 2);
+}
+
+#[test]
+fn test_space_saving_heavy_hitters() {
+    let mut summary = SpaceSaving::new(3);
+    for item in ["a", "a", "a", "a", "b", "b", "b", "c", "d", "e", "a", "b"] {
+        summary.add(item);
+    }
+
+    let top = summary.top(2);
+    assert_eq!(top[0].0, "a");
+    assert_eq!(top[1].0, "b");
+    for (item, counter) in &top {
+        let true_count = if *item == "a" { 5 } else { 4 };
+        assert!(counter.count >= true_count);
+        assert!(counter.count - counter.error <= true_count);
+    }
+    assert!(summary.error_bound() >= 1);
+    assert_eq!(summary.len(), 3);
+}
+
+#[test]
+fn test_space_saving_merge_bounds() {
+    let first = SpaceSaving::from_counts(2, vec![("a", 10), ("b", 6), ("c", 2)]);
+    let second = SpaceSaving::from_counts(2, vec![("a", 3), ("c", 5), ("d", 1)]);
+    assert_eq!(first.error_bound(), 6);
+
+    let mut merged = SpaceSaving::new(2);
+    merged.merge(&first);
+    merged.merge(&second);
+
+    // True counts: a = 13, b = 6, c = 7, d = 1
+    let top = merged.top(2);
+    assert_eq!(top[0].0, "a");
+    assert_eq!(
+        top[0].1,
+        Counter {
+            count: 13,
+            error: 0
+        }
+    );
+    for (item, counter) in &top {
+        let true_count = match *item {
+            "a" => 13,
+            "b" => 6,
+            _ => 7,
+        };
+        assert!(counter.count >= true_count);
+        assert!(counter.count - counter.error <= true_count);
+    }
+}
+
+#[test]
+fn test_route_analyzer_finalize() {
+    let mut analyzer = RouteAnalyzer::new(6, 1);
+    let mut trips = vec![create_test_trip(), create_test_trip(), create_test_trip()];
+    trips[2].dropoff_latitude = 40.70;
+    let mut outside = create_test_trip();
+    outside.dropoff_latitude = 0.0;
+    trips.push(outside);
+
+    let first = analyzer.process_batch(&trips[..2]).unwrap();
+    let second = analyzer.process_batch(&trips[2..]).unwrap();
+    analyzer.merge_accumulators(vec![first, second]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.trips_counted, 3);
+    assert_eq!(result.routes.len(), 1);
+    let route = &result.routes[0];
+    assert_eq!(route.pickup_cell.len(), 6);
+    assert!(route.trip_count >= 2);
+    assert!(route.min_trip_count <= 2);
+}
+
+#[test]
+fn test_time_series_rolling_and_deltas() {
+    let mut analyzer = TimeSeriesAnalyzer::with_quarter_hours(true);
+    // One trip on Jan 1, two on Jan 2, one on Jan 9
+    let mut trips = Vec::new();
+    for (day, minute) in [(1, 0), (2, 5), (2, 20), (9, 0)] {
+        let mut trip = create_test_trip();
+        trip.pickup_datetime = Utc.with_ymd_and_hms(2015, 1, day, 12, minute, 0).unwrap();
+        trips.push(trip);
+    }
+
+    let accumulator = analyzer.process_batch(&trips).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.daily.len(), 3);
+    let first = &result.daily[0];
+    assert_eq!(first.trips_delta, None);
+    assert_eq!(first.trips_7d_avg, 1.0);
+
+    let second = &result.daily[1];
+    assert_eq!(second.trip_count, 2);
+    assert_eq!(second.trips_delta, Some(1));
+    assert_eq!(second.revenue_delta, Some(19.3));
+    assert_eq!(second.trips_7d_avg, 1.5);
+
+    // Jan 9 is outside the window of Jan 1 and 2, and has no previous day
+    let third = &result.daily[2];
+    assert_eq!(third.trips_7d_avg, 1.0);
+    assert_eq!(third.trips_delta, None);
+
+    let quarter_hours = result.quarter_hours.unwrap();
+    assert_eq!(quarter_hours.len(), 4);
+    assert_eq!(
+        quarter_hours[1].start,
+        Utc.with_ymd_and_hms(2015, 1, 2, 12, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_time_series_without_quarter_hours() {
+    let mut analyzer = TimeSeriesAnalyzer::default();
+    let accumulator = analyzer.process_batch(&[create_test_trip()]).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.daily.len(), 1);
+    assert!(result.quarter_hours.is_none());
+}
+
+#[test]
+fn test_fare_component_analyzer_finalize() {
+    let mut analyzer = FareComponentAnalyzer::default();
+    let trip1 = create_test_trip();
+    let mut trip2 = create_test_trip();
+    trip2.tolls_amount = 5.54;
+    trip2.total_amount += 5.54;
+    let mut trip3 = create_test_trip();
+    trip3.payment_type = 2;
+    trip3.tip_amount = 0.0;
+    trip3.total_amount -= 3.0;
+
+    let accumulator = analyzer.process_batch(&[trip1, trip2, trip3]).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.len(), 2);
+    let card = &result[0];
+    assert_eq!(card.payment_type, 1);
+    assert_eq!(card.trip_count, 2);
+    assert_eq!(card.fare_amount, 30.0);
+    assert_eq!(card.tolls_amount, 5.54);
+    assert_eq!(card.total_amount, 44.14);
+    assert_eq!(card.unreconciled_amount, 0.0);
+    assert_eq!(card.surcharge_share, 5.89);
+
+    let cash = &result[1];
+    assert_eq!(cash.tip_share, 0.0);
+    assert_eq!(cash.fare_share, 92.02);
+}
+
+#[test]
+fn test_anomaly_analyzer_rule_flags() {
+    let mut analyzer = AnomalyAnalyzer::default();
+    let normal = create_test_trip();
+    let mut mismatch = create_test_trip();
+    mismatch.total_amount += 0.02;
+    let mut zero_distance = create_test_trip();
+    zero_distance.trip_distance = 0.0;
+    zero_distance.fare_amount = 80.0;
+    zero_distance.total_amount = 84.3;
+    let mut reversed = create_test_trip();
+    reversed.dropoff_datetime = reversed.pickup_datetime - chrono::Duration::minutes(5);
+    let mut too_long = create_test_trip();
+    too_long.dropoff_datetime = too_long.pickup_datetime + chrono::Duration::hours(25);
+    let mut too_fast = create_test_trip();
+    too_fast.dropoff_datetime = too_fast.pickup_datetime + chrono::Duration::minutes(2);
+
+    let accumulator = analyzer
+        .process_batch(&[
+            normal,
+            mismatch,
+            zero_distance,
+            reversed,
+            too_long,
+            too_fast,
+        ])
+        .unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    let summary = &result.summary;
+    assert_eq!(summary.trips_checked, 6);
+    assert_eq!(summary.trips_flagged, 5);
+    assert!(!summary.fare_outliers_checked);
+    for reason in [
+        "TOTAL_MISMATCH",
+        "ZERO_DISTANCE_HIGH_FARE",
+        "NEGATIVE_DURATION",
+        "DURATION_OVER_24H",
+        "SPEED_OVER_100MPH",
+    ] {
+        assert_eq!(summary.reason_counts.get(reason), Some(&1));
+    }
+    assert!(!summary.reason_counts.contains_key("FARE_OUTLIER"));
+}
+
+#[test]
+fn test_anomaly_analyzer_fare_outliers() {
+    let trips: Vec<TaxiTrip> = (0..40)
+        .map(|i| {
+            let mut trip = create_test_trip();
+            trip.fare_amount = 10.0 + (i % 5) as f64;
+            trip.total_amount = trip.fare_amount + 4.3;
+            trip
+        })
+        .collect();
+
+    let mut fence_analyzer = FareFenceAnalyzer::default();
+    let accumulator = fence_analyzer.process_batch(&trips).unwrap();
+    fence_analyzer
+        .merge_accumulators(vec![accumulator])
+        .unwrap();
+    let fences = fence_analyzer.finalize().unwrap();
+    assert_eq!(fences.len(), 1);
+
+    let mut outlier = create_test_trip();
+    outlier.fare_amount = 400.0;
+    outlier.total_amount = 404.3;
+
+    let mut analyzer = AnomalyAnalyzer::with_fences(Some(std::sync::Arc::new(fences)));
+    let accumulator = analyzer
+        .process_batch(&[trips[0].clone(), outlier])
+        .unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert!(result.summary.fare_outliers_checked);
+    assert_eq!(result.flagged_trips.len(), 1);
+    assert_eq!(result.flagged_trips[0].reasons, "FARE_OUTLIER");
+    assert_eq!(result.flagged_trips[0].fare_amount, 400.0);
+}
+
+#[test]
+fn test_tariff_schedule_selects_tariff_in_effect() {
+    let schedule = TariffSchedule::default();
+    let trip = create_test_trip();
+    let tariff = schedule
+        .tariff_for(trip.pickup_datetime.date_naive())
+        .unwrap();
+
+    // 2.50 flag drop + 25 fifths of a mile + 5 minutes of slow traffic
+    assert_eq!(tariff.expected_fare(&trip), 17.5);
+    assert_eq!(tariff.expected_surcharge(&trip), 0.0);
+
+    let before = chrono::NaiveDate::from_ymd_opt(2012, 1, 1).unwrap();
+    assert!(schedule.tariff_for(before).is_none());
+
+    let mut raised = tariff.clone();
+    raised.effective_date = chrono::NaiveDate::from_ymd_opt(2014, 6, 1).unwrap();
+    raised.flag_drop = 3.0;
+    let schedule = TariffSchedule::new(vec![raised, tariff.clone()]);
+    let tariff = schedule
+        .tariff_for(trip.pickup_datetime.date_naive())
+        .unwrap();
+    assert_eq!(tariff.flag_drop, 3.0);
+}
+
+#[test]
+fn test_tariff_analyzer_discrepancies() {
+    let mut overcharged = create_test_trip();
+    overcharged.fare_amount = 30.0;
+
+    let mut negotiated = create_test_trip();
+    negotiated.rate_code_id = 5;
+    negotiated.fare_amount = 80.0;
+
+    let mut analyzer = TariffAnalyzer::with_schedule(TariffSchedule::default());
+    let accumulator = analyzer
+        .process_batch(&[create_test_trip(), overcharged, negotiated])
+        .unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.len(), 1);
+    let discrepancy = &result[0];
+    assert_eq!(discrepancy.vendor_id, 1);
+    assert_eq!(discrepancy.hour, 12);
+    assert_eq!(discrepancy.trips_compared, 2);
+    assert_eq!(discrepancy.avg_expected_fare, 17.5);
+    assert_eq!(discrepancy.avg_fare, 22.5);
+    assert_eq!(discrepancy.avg_difference, 5.0);
+    assert_eq!(discrepancy.overcharged_trips, 1);
+    assert_eq!(discrepancy.undercharged_trips, 0);
+    assert_eq!(discrepancy.overcharge_percentage, 50.0);
+    assert_eq!(discrepancy.total_overcharge, 12.5);
+    assert_eq!(discrepancy.avg_extra, 0.5);
+}
+
+#[test]
+fn test_tip_analyzer_separates_cash_trips() {
+    let mut untipped = create_test_trip();
+    untipped.tip_amount = 0.0;
+    untipped.trip_distance = 0.5;
+
+    let mut cash = create_test_trip();
+    cash.payment_type = 2;
+    cash.tip_amount = 0.0;
+
+    let mut analyzer = TipAnalyzer::default();
+    let accumulator = analyzer
+        .process_batch(&[create_test_trip(), untipped, cash])
+        .unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.summary.trip_count, 2);
+    assert_eq!(result.summary.tipped_trip_percentage, 50.0);
+    assert_eq!(result.summary.avg_tip, 1.5);
+    assert_eq!(result.summary.avg_tip_percentage, 10.0);
+
+    assert_eq!(result.cash.trip_count, 1);
+    assert_eq!(result.cash.trip_percentage, 33.33);
+    assert_eq!(result.cash.trips_with_recorded_tip, 0);
+
+    assert_eq!(result.by_payment_type.len(), 1);
+    assert_eq!(result.by_payment_type[0].group, "1");
+    assert_eq!(result.by_hour[0].group, "12");
+    assert_eq!(result.by_hour[0].stats.trip_count, 2);
+    assert_eq!(result.by_passenger_count[0].group, "1");
+
+    let bands: Vec<&str> = result
+        .by_distance_band
+        .iter()
+        .map(|group| group.group.as_str())
+        .collect();
+    assert_eq!(bands, vec!["0-1", "5-10"]);
+    assert_eq!(result.by_distance_band[1].stats.avg_tip_percentage, 20.0);
+}
+
+#[test]
+fn test_store_forward_analyzer_compares_trips() {
+    let mut stored = create_test_trip();
+    stored.store_and_fwd_flag = Some("Y".to_string());
+    stored.fare_amount = 25.0;
+    stored.dropoff_datetime = stored.pickup_datetime + chrono::Duration::minutes(40);
+
+    let mut analyzer = StoreForwardAnalyzer::default();
+    let accumulator = analyzer
+        .process_batch(&[create_test_trip(), create_test_trip(), stored])
+        .unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.len(), 1);
+    let stats = &result[0];
+    assert_eq!(stats.vendor_id, 1);
+    assert_eq!(stats.hour, 12);
+    assert_eq!(stats.trip_count, 3);
+    assert_eq!(stats.store_and_forward_trips, 1);
+    assert_eq!(stats.store_and_forward_rate, 33.33);
+    assert_eq!(stats.avg_fare_live, Some(15.0));
+    assert_eq!(stats.avg_fare_stored, Some(25.0));
+    assert_eq!(stats.fare_difference, Some(10.0));
+    assert_eq!(stats.avg_duration_live, Some(30.0));
+    assert_eq!(stats.duration_difference, Some(10.0));
+}
+
+#[test]
+fn test_store_forward_analyzer_without_stored_trips() {
+    let mut analyzer = StoreForwardAnalyzer::default();
+    let accumulator = analyzer.process_batch(&[create_test_trip()]).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result[0].store_and_forward_rate, 0.0);
+    assert_eq!(result[0].avg_fare_live, Some(15.0));
+    assert_eq!(result[0].avg_fare_stored, None);
+    assert_eq!(result[0].fare_difference, None);
+    assert_eq!(result[0].avg_duration_live, Some(30.0));
+    assert_eq!(result[0].avg_duration_stored, None);
+    assert_eq!(result[0].duration_difference, None);
+}
+
+#[test]
+fn test_concurrency_analyzer_sweeps_merged_batches() {
+    let mut overlapping = create_test_trip();
+    overlapping.pickup_datetime = Utc.with_ymd_and_hms(2015, 1, 1, 12, 10, 0).unwrap();
+    overlapping.dropoff_datetime = Utc.with_ymd_and_hms(2015, 1, 1, 12, 20, 0).unwrap();
+
+    let mut overnight = create_test_trip();
+    overnight.pickup_datetime = Utc.with_ymd_and_hms(2015, 1, 2, 23, 50, 0).unwrap();
+    overnight.dropoff_datetime = Utc.with_ymd_and_hms(2015, 1, 3, 0, 10, 0).unwrap();
+
+    let mut analyzer = ConcurrencyAnalyzer::default();
+    let first = analyzer
+        .process_batch(&[create_test_trip(), overnight])
+        .unwrap();
+    let second = analyzer.process_batch(&[overlapping]).unwrap();
+    analyzer.merge_accumulators(vec![first, second]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.minutes.len(), 50);
+    assert_eq!(result.minutes[0].active_trips, 1);
+    assert_eq!(result.minutes[10].active_trips, 2);
+
+    assert_eq!(result.daily.len(), 3);
+    let first_day = &result.daily[0];
+    assert_eq!(first_day.active_minutes, 30);
+    assert_eq!(first_day.peak_active_trips, 2);
+    assert_eq!(
+        first_day.peak_minute,
+        Utc.with_ymd_and_hms(2015, 1, 1, 12, 10, 0).unwrap()
+    );
+    assert_eq!(first_day.avg_active_trips, 0.03);
+    assert_eq!(result.daily[2].peak_active_trips, 1);
+}
+
+#[test]
+fn test_net_flow_analyzer_series() {
+    let mut first = create_test_trip();
+    first.dropoff_latitude = first.pickup_latitude;
+    first.dropoff_longitude = first.pickup_longitude;
+
+    let mut second = first.clone();
+    second.pickup_datetime = Utc.with_ymd_and_hms(2015, 1, 1, 12, 50, 0).unwrap();
+    second.dropoff_datetime = Utc.with_ymd_and_hms(2015, 1, 1, 13, 10, 0).unwrap();
+
+    let mut analyzer = NetFlowAnalyzer::default();
+    let accumulator = analyzer.process_batch(&[first, second]).unwrap();
+    analyzer.merge_accumulators(vec![accumulator]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.len(), 1);
+    let series = &result[0].series;
+    assert_eq!(series.len(), 2);
+    assert_eq!(
+        series[0].hour,
+        Utc.with_ymd_and_hms(2015, 1, 1, 12, 0, 0).unwrap()
+    );
+    assert_eq!((series[0].pickups, series[0].dropoffs), (2, 1));
+    assert_eq!(series[0].net_flow, 1);
+    assert_eq!((series[1].pickups, series[1].dropoffs), (0, 1));
+    assert_eq!(series[1].net_flow, -1);
+    assert_eq!(series[1].cumulative_net_flow, 0);
+}
+
+#[test]
+fn test_anomaly_analyzer_caps_flagged_trips() {
+    let trips: Vec<TaxiTrip> = (0..5)
+        .rev()
+        .map(|hour| {
+            let mut trip = create_test_trip();
+            trip.pickup_datetime = Utc.with_ymd_and_hms(2015, 1, 1, hour, 0, 0).unwrap();
+            trip.dropoff_datetime = Utc.with_ymd_and_hms(2015, 1, 1, hour, 30, 0).unwrap();
+            trip.total_amount = 100.0;
+            trip
+        })
+        .collect();
+
+    let mut analyzer = AnomalyAnalyzer::default().with_max_flagged(2);
+    let first = analyzer.process_batch(&trips[..3]).unwrap();
+    let second = analyzer.process_batch(&trips[3..]).unwrap();
+    analyzer.merge_accumulators(vec![first, second]).unwrap();
+    let result = analyzer.finalize().unwrap();
+
+    assert_eq!(result.summary.trips_flagged, 5);
+    assert_eq!(result.summary.trips_listed, 2);
+    assert_eq!(result.summary.reason_counts.get("TOTAL_MISMATCH"), Some(&5));
+    let hours: Vec<u32> = result
+        .flagged_trips
+        .iter()
+        .map(|flag| chrono::Timelike::hour(&flag.pickup_datetime))
+        .collect();
+    assert_eq!(hours, vec![0, 1]);
+}